@@ -0,0 +1,109 @@
+//
+// split.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use criterion::{
+    criterion_group,
+    criterion_main,
+    Criterion,
+};
+use devgen_splitter::{
+    split,
+    SplitOptions,
+    Splitter,
+};
+use std::hint::black_box;
+use tree_sitter::Query;
+
+const CASES: &[(&str, &str)] = &[
+    (
+        "rust_long_function.rs",
+        include_str!("../tests/cases/rust/rust_long_function.rs"),
+    ),
+    (
+        "test_java.java",
+        include_str!("../tests/cases/java/test_java.java"),
+    ),
+    (
+        "typescript_react_test.ts",
+        include_str!("../tests/cases/ts/typescript_react_test.ts"),
+    ),
+    (
+        "test_octogen.py",
+        include_str!("../tests/cases/python/test_octogen.py"),
+    ),
+];
+
+/// splits the same files again and again, the queries and the parser are reused
+fn bench_split(c: &mut Criterion) {
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        ..Default::default()
+    };
+    let mut group = c.benchmark_group("split");
+    for (filename, code) in CASES {
+        group.bench_function(*filename, |b| {
+            b.iter(|| split(black_box(filename), black_box(code), &options).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// splits the same files with a reusable splitter and its pool of parsers
+fn bench_splitter(c: &mut Criterion) {
+    let splitter = Splitter::builder()
+        .options(SplitOptions {
+            chunk_line_limit: 40,
+            ..Default::default()
+        })
+        .build();
+    let mut group = c.benchmark_group("splitter");
+    for (filename, code) in CASES {
+        group.bench_function(*filename, |b| {
+            b.iter(|| {
+                splitter
+                    .split(black_box(filename), black_box(code))
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+/// the cost every split paid before the compiled queries were cached
+fn bench_query_compile(c: &mut Criterion) {
+    let queries = [
+        (
+            "rust",
+            tree_sitter_rust::language(),
+            include_str!("../queries/rust.scm"),
+        ),
+        (
+            "java",
+            tree_sitter_java::language(),
+            include_str!("../queries/java.scm"),
+        ),
+        (
+            "typescript",
+            tree_sitter_typescript::language_tsx(),
+            include_str!("../queries/typescript.scm"),
+        ),
+        (
+            "python",
+            tree_sitter_python::language(),
+            include_str!("../queries/python.scm"),
+        ),
+    ];
+    let mut group = c.benchmark_group("query_compile");
+    for (name, language, query) in queries.iter() {
+        group.bench_function(*name, |b| {
+            b.iter(|| Query::new(black_box(language), black_box(query)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_split, bench_splitter, bench_query_compile);
+criterion_main!(benches);
@@ -35,6 +35,7 @@ console.log(greeter.greet());
 
     let options = SplitOptions {
         chunk_line_limit: 5,
+        ..Default::default()
     };
     println!("Splitting Rust code:");
     let rust_chunks = split("example.rs", rust_code, &options).unwrap();
@@ -0,0 +1,11 @@
+; Import query, every spec of a grouped import is an import
+(import_spec) @import.statement
+
+; Name query
+(import_spec
+  name: (package_identifier) @import.name)
+
+; the package name of an import without alias is the last element of its path
+(import_spec
+  !name
+  path: (interpreted_string_literal) @import.path)
@@ -0,0 +1,6 @@
+; Import query
+(import_declaration) @import.statement
+
+; Name query
+(import_declaration
+  (scoped_identifier name: (identifier) @import.name))
@@ -0,0 +1,14 @@
+; Import query
+(import_statement) @import.statement
+
+(import_from_statement) @import.statement
+
+; Name query
+(import_statement
+  name: (dotted_name . (identifier) @import.name))
+
+(import_from_statement
+  name: (dotted_name) @import.name)
+
+(aliased_import
+  alias: (identifier) @import.name)
@@ -0,0 +1,18 @@
+; Import query
+(use_declaration) @import.statement
+
+; Name query
+(use_declaration
+  argument: [
+    (identifier) @import.name
+    (scoped_identifier name: (identifier) @import.name)
+  ])
+
+(use_as_clause
+  alias: (identifier) @import.name)
+
+(use_list
+  [
+    (identifier) @import.name
+    (scoped_identifier name: (identifier) @import.name)
+  ])
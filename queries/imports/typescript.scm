@@ -0,0 +1,19 @@
+; Import query
+(import_statement) @import.statement
+
+; Name query
+(import_clause
+  (identifier) @import.name)
+
+(import_specifier
+  !alias
+  name: (identifier) @import.name)
+
+(import_specifier
+  alias: (identifier) @import.name)
+
+(namespace_import
+  (identifier) @import.name)
+
+(import_require_clause
+  (identifier) @import.name)
@@ -0,0 +1,13 @@
+; Call query
+(call_expression
+  function: [
+    (identifier) @reference.call
+    (selector_expression field: (field_identifier) @reference.call)
+  ])
+
+; Type query
+(type_identifier) @reference.type
+
+; Module query
+(import_spec
+  path: (interpreted_string_literal) @reference.module)
@@ -0,0 +1,13 @@
+; Call query
+(method_invocation
+  name: (identifier) @reference.call)
+
+; Type query
+(type_identifier) @reference.type
+
+; Module query
+(import_declaration
+  [
+    (scoped_identifier) @reference.module
+    (identifier) @reference.module
+  ])
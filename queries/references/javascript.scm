@@ -0,0 +1,20 @@
+; Call query
+(call_expression
+  function: [
+    (identifier) @reference.call
+    (member_expression property: (property_identifier) @reference.call)
+  ])
+
+; Type query
+(new_expression
+  constructor: [
+    (identifier) @reference.type
+    (member_expression property: (property_identifier) @reference.type)
+  ])
+
+(class_heritage
+  (identifier) @reference.type)
+
+; Module query
+(import_statement
+  source: (string (string_fragment) @reference.module))
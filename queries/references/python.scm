@@ -0,0 +1,24 @@
+; Call query
+(call
+  function: [
+    (identifier) @reference.call
+    (attribute attribute: (identifier) @reference.call)
+  ])
+
+; Type query
+(type
+  (identifier) @reference.type)
+
+(class_definition
+  superclasses: (argument_list
+    (identifier) @reference.type))
+
+; Module query
+(import_statement
+  name: [
+    (dotted_name) @reference.module
+    (aliased_import name: (dotted_name) @reference.module)
+  ])
+
+(import_from_statement
+  module_name: (_) @reference.module)
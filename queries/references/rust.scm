@@ -0,0 +1,23 @@
+; Call query
+(call_expression
+  function: [
+    (identifier) @reference.call
+    (field_expression field: (field_identifier) @reference.call)
+    (scoped_identifier name: (identifier) @reference.call)
+    (generic_function
+      function: [
+        (identifier) @reference.call
+        (field_expression field: (field_identifier) @reference.call)
+        (scoped_identifier name: (identifier) @reference.call)
+      ])
+  ])
+
+(macro_invocation
+  macro: (identifier) @reference.call)
+
+; Type query
+(type_identifier) @reference.type
+
+; Module query
+(use_declaration
+  argument: (_) @reference.module)
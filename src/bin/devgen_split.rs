@@ -0,0 +1,382 @@
+//
+// devgen_split.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use anyhow::Result;
+use clap::{
+    Parser,
+    ValueEnum,
+};
+use devgen_splitter::{
+    split_dir,
+    Chunk,
+    ChunkOverlap,
+    GeneratedPolicy,
+    Lang,
+    Notebook,
+    SplitOptions,
+    Splitter,
+    WalkOptions,
+};
+use serde::Serialize;
+use std::{
+    io::{
+        self,
+        BufWriter,
+        Read,
+        Write,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+    process::ExitCode,
+    sync::Arc,
+};
+
+/// Splits source files, directories or stdin into contextual chunks
+#[derive(Debug, Parser)]
+#[command(name = "devgen-split", version, about)]
+struct Args {
+    /// The files or directories to split, `-` or nothing reads the code from stdin
+    paths: Vec<PathBuf>,
+    /// The language of the code read from stdin, a name or an extension, e.g. `rust` or `rs`
+    #[arg(long)]
+    lang: Option<String>,
+    /// The output format
+    #[arg(long, value_enum, default_value_t = Format::Jsonl)]
+    format: Format,
+    /// The maximum number of lines of a chunk
+    #[arg(long, default_value_t = 40)]
+    chunk_lines: usize,
+    /// The maximum number of tokens of a chunk, estimated as four characters per token
+    #[arg(long)]
+    max_tokens: Option<usize>,
+    /// The overlap between consecutive chunks, a line count (`3`) or a fraction (`0.1` or
+    /// `1e-1`)
+    #[arg(long, value_parser = parse_overlap)]
+    overlap: Option<ChunkOverlap>,
+    /// Only split the files matching the glob when walking a directory, can be repeated
+    #[arg(long)]
+    include: Vec<String>,
+    /// Skip the files matching the glob when walking a directory, can be repeated
+    #[arg(long)]
+    exclude: Vec<String>,
+    /// Include the text of every chunk in the output
+    #[arg(long)]
+    with_text: bool,
+    /// Recover the entities around the syntax errors of the code
+    #[arg(long)]
+    tolerate_errors: bool,
+    /// What to do with the minified and generated files
+    #[arg(long, value_enum, default_value_t = Generated::Ignore)]
+    generated: Generated,
+    /// Keep the text outputs of the notebook cells as chunks
+    #[arg(long)]
+    keep_notebook_outputs: bool,
+    /// Find the SQL queries embedded in the string literals of Python, TypeScript and
+    /// JavaScript code
+    #[arg(long)]
+    embedded_sql: bool,
+    /// Collect the functions, types and modules referenced by every chunk
+    #[arg(long)]
+    references: bool,
+    /// Extract the imports and attach to every chunk the imports it uses
+    #[arg(long)]
+    imports: bool,
+    /// The number of threads used to split a directory, `0` picks one per CPU
+    #[arg(long, default_value_t = 0)]
+    threads: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    /// one JSON object per chunk and per line
+    Jsonl,
+    /// a human readable block per chunk
+    Pretty,
+    /// one row per chunk
+    Table,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Generated {
+    /// split them like any other file
+    Ignore,
+    /// don't print any chunk for them
+    Skip,
+    /// split them by lines only
+    Coarse,
+    /// split them like any other file and mark their chunks as generated
+    Tag,
+}
+
+impl From<Generated> for GeneratedPolicy {
+    fn from(generated: Generated) -> Self {
+        match generated {
+            Generated::Ignore => GeneratedPolicy::Ignore,
+            Generated::Skip => GeneratedPolicy::Skip,
+            Generated::Coarse => GeneratedPolicy::Coarse,
+            Generated::Tag => GeneratedPolicy::Tag,
+        }
+    }
+}
+
+fn parse_overlap(value: &str) -> Result<ChunkOverlap, String> {
+    if let Ok(lines) = value.parse::<usize>() {
+        return Ok(ChunkOverlap::Lines(lines));
+    }
+    let ratio = value.parse::<f64>().map_err(|e| e.to_string())?;
+    if !ratio.is_finite() || ratio < 0.0 {
+        return Err("the overlap ratio must be a finite positive number".to_string());
+    }
+    Ok(ChunkOverlap::Ratio(ratio as f32))
+}
+
+/// A chunk as printed in the JSON Lines output
+#[derive(Serialize)]
+struct ChunkRecord<'a> {
+    path: &'a str,
+    #[serde(flatten)]
+    chunk: &'a Chunk,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+/// Prints the chunks in the output format, the table is printed at the end
+struct Printer {
+    format: Format,
+    with_text: bool,
+    out: BufWriter<io::Stdout>,
+    rows: Vec<[String; 3]>,
+}
+
+impl Printer {
+    /// Returns the text of every chunk when they are printed, an invalid notebook fails
+    fn texts(&self, code: &str, chunks: &[Chunk]) -> Result<Option<Vec<String>>> {
+        if !self.with_text {
+            return Ok(None);
+        }
+        let lines = code.lines().collect::<Vec<&str>>();
+        // the text of a notebook chunk is read from its cell
+        let notebook = match chunks.iter().any(|chunk| chunk.cell.is_some()) {
+            true => Some(Notebook::parse(code)?),
+            false => None,
+        };
+        let texts = chunks
+            .iter()
+            .map(|chunk| {
+                let cell_lines;
+                let (lines, line_range): (&[&str], _) = match (&notebook, &chunk.cell) {
+                    (Some(notebook), Some(cell)) => {
+                        cell_lines = notebook
+                            .text(cell)
+                            .unwrap_or_default()
+                            .lines()
+                            .collect::<Vec<&str>>();
+                        (&cell_lines, &cell.line_range)
+                    }
+                    _ => (&lines, &chunk.line_range),
+                };
+                let end = line_range.end.min(lines.len());
+                lines[line_range.start.min(end)..end].join("\n")
+            })
+            .collect();
+        Ok(Some(texts))
+    }
+
+    fn print(&mut self, path: &str, chunks: &[Chunk], texts: Option<Vec<String>>) -> Result<()> {
+        let mut texts = texts.map(|texts| texts.into_iter());
+        for chunk in chunks {
+            let text = texts.as_mut().and_then(|texts| texts.next());
+            let entities = chunk
+                .entities
+                .iter()
+                .map(|entity| match &entity.parent {
+                    Some(parent) => format!("{:?} {}::{}", entity.entity_type, parent, entity.name),
+                    None => format!("{:?} {}", entity.entity_type, entity.name),
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            match self.format {
+                Format::Jsonl => {
+                    let record = ChunkRecord { path, chunk, text };
+                    serde_json::to_writer(&mut self.out, &record)?;
+                    writeln!(self.out)?;
+                }
+                Format::Pretty => {
+                    writeln!(
+                        self.out,
+                        "== {} [{}..{}] ==",
+                        path, chunk.line_range.start, chunk.line_range.end
+                    )?;
+                    if !entities.is_empty() {
+                        writeln!(self.out, "entities: {}", entities)?;
+                    }
+                    if !chunk.references.is_empty() {
+                        let references = chunk
+                            .references
+                            .iter()
+                            .map(|reference| format!("{:?} {}", reference.kind, reference.name))
+                            .collect::<Vec<String>>();
+                        writeln!(self.out, "references: {}", references.join(", "))?;
+                    }
+                    if !chunk.imports.is_empty() {
+                        let imports = chunk
+                            .imports
+                            .iter()
+                            .map(|import| import.names.join(", "))
+                            .collect::<Vec<String>>();
+                        writeln!(self.out, "imports: {}", imports.join(", "))?;
+                    }
+                    if let Some(text) = text {
+                        writeln!(self.out, "{}", text)?;
+                    }
+                    writeln!(self.out)?;
+                }
+                Format::Table => self.rows.push([
+                    path.to_string(),
+                    format!("{}..{}", chunk.line_range.start, chunk.line_range.end),
+                    entities,
+                ]),
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<()> {
+        if self.format == Format::Table {
+            let header = [
+                "PATH".to_string(),
+                "LINES".to_string(),
+                "ENTITIES".to_string(),
+            ];
+            let path_width = self
+                .rows
+                .iter()
+                .chain([&header])
+                .map(|row| row[0].len())
+                .max();
+            let lines_width = self
+                .rows
+                .iter()
+                .chain([&header])
+                .map(|row| row[1].len())
+                .max();
+            for row in [&header].into_iter().chain(self.rows.iter()) {
+                let line = format!(
+                    "{:path_width$}  {:lines_width$}  {}",
+                    row[0],
+                    row[1],
+                    row[2],
+                    path_width = path_width.unwrap_or(0),
+                    lines_width = lines_width.unwrap_or(0)
+                );
+                writeln!(self.out, "{}", line.trim_end())?;
+            }
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+fn run(args: Args) -> Result<bool> {
+    let mut builder = Splitter::builder().options(SplitOptions {
+        chunk_line_limit: args.chunk_lines,
+        overlap: args.overlap,
+        tolerate_errors: args.tolerate_errors,
+        generated: args.generated.into(),
+        keep_notebook_outputs: args.keep_notebook_outputs,
+        embedded_sql: args.embedded_sql,
+        collect_references: args.references,
+        collect_imports: args.imports,
+        ..Default::default()
+    });
+    if let Some(max_tokens) = args.max_tokens {
+        builder = builder.max_tokens(max_tokens);
+    }
+    let splitter = Arc::new(builder.build());
+    let mut printer = Printer {
+        format: args.format,
+        with_text: args.with_text,
+        out: BufWriter::new(io::stdout()),
+        rows: vec![],
+    };
+    let mut failed = false;
+    let mut report = |path: &str, error: anyhow::Error| {
+        eprintln!("devgen-split: {}: {}", path, error);
+        failed = true;
+    };
+    let paths = if args.paths.is_empty() {
+        vec![PathBuf::from("-")]
+    } else {
+        args.paths.clone()
+    };
+    for path in paths {
+        if path == Path::new("-") {
+            let Some(lang_config) = args.lang.as_deref().and_then(Lang::from_name) else {
+                anyhow::bail!("--lang with a supported language is required to split stdin");
+            };
+            let mut code = String::new();
+            io::stdin().read_to_string(&mut code)?;
+            let filename = format!("stdin.{}", lang_config.file_extensions[0]);
+            let result = splitter
+                .split(&filename, &code)
+                .map_err(anyhow::Error::from)
+                .and_then(|chunks| Ok((printer.texts(&code, &chunks)?, chunks)));
+            match result {
+                Ok((texts, chunks)) => printer.print("-", &chunks, texts)?,
+                Err(e) => report("-", e),
+            }
+        } else if path.is_dir() {
+            let walk_options = WalkOptions {
+                threads: args.threads,
+                include: args.include.clone(),
+                exclude: args.exclude.clone(),
+                ..Default::default()
+            };
+            for (path, chunks) in split_dir(&path, splitter.clone(), &walk_options)? {
+                let display = path.to_string_lossy();
+                // the code is only needed to print the text of the chunks
+                let result = chunks.map_err(anyhow::Error::from).and_then(|chunks| {
+                    let code = match args.with_text {
+                        true => std::fs::read_to_string(&path)?,
+                        false => String::new(),
+                    };
+                    Ok((printer.texts(&code, &chunks)?, chunks))
+                });
+                match result {
+                    Ok((texts, chunks)) => printer.print(&display, &chunks, texts)?,
+                    Err(e) => report(&display, e),
+                }
+            }
+        } else {
+            let display = path.to_string_lossy();
+            let result = std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|code| {
+                    let chunks = splitter.split(&display, &code)?;
+                    Ok((printer.texts(&code, &chunks)?, chunks))
+                });
+            match result {
+                Ok((texts, chunks)) => printer.print(&display, &chunks, texts)?,
+                Err(e) => report(&display, e),
+            }
+        }
+    }
+    printer.finish()?;
+    Ok(!failed)
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("devgen-split: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
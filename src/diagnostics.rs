@@ -0,0 +1,259 @@
+//
+// diagnostics.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use crate::{
+    lang::LangConfig,
+    splitter::{
+        cache,
+        ranges,
+    },
+    Chunk,
+    GeneratedKind,
+    Limit,
+    Result,
+    SplitError,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    cell::RefCell,
+    ops::Range,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use tree_sitter::{
+    Node,
+    Parser,
+    Tree,
+};
+
+/// the maximum number of lines tried while recovering from syntax errors
+const MAX_RECOVERY_ROUNDS: usize = 32;
+
+/// The kind of a syntax error found by the parser
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SyntaxErrorKind {
+    /// the code can't be parsed, tree-sitter skipped it with an `ERROR` node
+    Error,
+    /// a token is missing, tree-sitter inserted a `MISSING` node in its place
+    Missing {
+        /// the kind of the missing node, e.g. `"}"`
+        node_kind: String,
+    },
+}
+
+/// A syntax error found by the parser
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyntaxError {
+    /// the kind of the error
+    pub kind: SyntaxErrorKind,
+    /// the lines of the error node
+    pub line_range: Range<usize>,
+    /// the bytes of the error node, empty for a missing node
+    pub byte_range: Range<usize>,
+    /// the index of the notebook cell holding the error, its lines are then the lines of
+    /// the notebook file and its bytes the bytes of the source of the cell
+    #[serde(default)]
+    pub cell: Option<usize>,
+}
+
+/// What happened while splitting a file, mostly about the syntax errors of the code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostics {
+    /// the outermost `ERROR` nodes and the `MISSING` nodes of the syntax tree
+    pub syntax_errors: Vec<SyntaxError>,
+    /// the number of entities captured by the query that couldn't be converted
+    pub failed_entities: usize,
+    /// no entity was found, the chunks follow the syntax nodes and the lines only
+    pub line_fallback: bool,
+    /// the lines masked to recover the entities around the syntax errors, only set
+    /// when `SplitOptions::tolerate_errors` is enabled
+    pub masked_line_ranges: Vec<Range<usize>>,
+    /// the limit exceeded by the file, the file is then split by lines only
+    pub exceeded_limit: Option<Limit>,
+    /// the file is minified or generated, see `SplitOptions::generated`
+    pub generated: Option<GeneratedKind>,
+}
+
+/// The chunks of a file and the diagnostics of the split
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitResult {
+    /// the chunks, as returned by `split`
+    pub chunks: Vec<Chunk>,
+    /// the diagnostics of the split
+    pub diagnostics: Diagnostics,
+}
+
+/// Returns the outermost `ERROR` nodes and the `MISSING` nodes of the tree
+pub(crate) fn syntax_errors(tree: &Tree) -> Vec<SyntaxError> {
+    let mut errors = vec![];
+    collect_syntax_errors(&tree.root_node(), &mut errors);
+    errors
+}
+
+fn collect_syntax_errors(node: &Node, errors: &mut Vec<SyntaxError>) {
+    let line_range = node.start_position().row..node.end_position().row + 1;
+    if node.is_error() {
+        errors.push(SyntaxError {
+            kind: SyntaxErrorKind::Error,
+            line_range,
+            byte_range: node.byte_range(),
+            cell: None,
+        });
+        return;
+    }
+    if node.is_missing() {
+        errors.push(SyntaxError {
+            kind: SyntaxErrorKind::Missing {
+                node_kind: node.kind().to_string(),
+            },
+            line_range,
+            byte_range: node.byte_range(),
+            cell: None,
+        });
+        return;
+    }
+    if !node.has_error() {
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_syntax_errors(&child, errors);
+    }
+}
+
+/// Measures the syntax errors of the tree by the bytes they cover, a missing node
+/// counts as one byte
+fn error_weight(tree: &Tree) -> usize {
+    syntax_errors(tree)
+        .iter()
+        .map(|error| error.byte_range.len().max(1))
+        .sum()
+}
+
+/// The code with the lines of its syntax errors masked, and its syntax tree
+pub(crate) struct Recovered {
+    pub code: String,
+    pub tree: Tree,
+    pub masked_line_ranges: Vec<Range<usize>>,
+}
+
+thread_local! {
+    /// the parser used to reparse the masked code on the current thread
+    static RECOVERY_PARSER: RefCell<Parser> = RefCell::new(Parser::new());
+}
+
+/// Masks the lines of the syntax errors with spaces, one line at a time, and
+/// reparses the code until it parses cleanly
+///
+/// A masked line is kept only if it shrinks the syntax errors, so the valid code
+/// around them is left alone. A masked line keeps its length and its newline, so the
+/// line numbers and the byte offsets of the other lines don't change. The reparses
+/// share the timeout, the lines masked so far are kept once it is spent.
+pub(crate) fn recover(
+    lang_config: &LangConfig,
+    tree: &Tree,
+    code: &str,
+    timeout: Option<Duration>,
+) -> Result<Recovered> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut masked = code.as_bytes().to_vec();
+    let mut tree = tree.clone();
+    let mut weight = error_weight(&tree);
+    let mut tried_lines = vec![];
+    let mut masked_lines = vec![];
+    let line_starts = ranges::line_starts(code);
+    for _ in 0..MAX_RECOVERY_ROUNDS {
+        if weight == 0 {
+            break;
+        }
+        let line = syntax_errors(&tree)
+            .into_iter()
+            .flat_map(|error| error.line_range)
+            .find(|line| !tried_lines.contains(line));
+        let Some(line) = line else {
+            break;
+        };
+        tried_lines.push(line);
+        let start = line_starts.get(line).copied().unwrap_or(masked.len());
+        let end = line_starts.get(line + 1).copied().unwrap_or(masked.len());
+        let mut candidate = masked.clone();
+        for byte in candidate[start..end].iter_mut() {
+            if *byte != b'\n' && *byte != b'\r' {
+                *byte = b' ';
+            }
+        }
+        let remaining = match deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) if !remaining.is_zero() => Some(remaining),
+                _ => break,
+            },
+            None => None,
+        };
+        // only whole lines are masked, so the candidate stays valid UTF-8
+        let candidate_code = std::str::from_utf8(&candidate).map_err(SplitError::InvalidUtf8)?;
+        let candidate_tree = RECOVERY_PARSER.with(|parser| {
+            cache::parse_with(
+                &mut parser.borrow_mut(),
+                lang_config,
+                candidate_code,
+                remaining,
+            )
+        });
+        let candidate_tree = match candidate_tree {
+            Ok(tree) => tree,
+            Err(SplitError::Timeout) => break,
+            Err(e) => return Err(e),
+        };
+        let candidate_weight = error_weight(&candidate_tree);
+        if candidate_weight < weight {
+            masked = candidate;
+            tree = candidate_tree;
+            weight = candidate_weight;
+            masked_lines.push(line);
+        }
+    }
+    masked_lines.sort_unstable();
+    let mut masked_line_ranges: Vec<Range<usize>> = vec![];
+    for line in masked_lines {
+        match masked_line_ranges.last_mut() {
+            Some(range) if range.end == line => range.end = line + 1,
+            _ => masked_line_ranges.push(line..line + 1),
+        }
+    }
+    Ok(Recovered {
+        // only whole lines are masked, so the code stays valid UTF-8
+        code: String::from_utf8(masked).map_err(|e| SplitError::InvalidUtf8(e.utf8_error()))?,
+        tree,
+        masked_line_ranges,
+    })
+}
+
+#[cfg(all(test, feature = "lang-rust"))]
+mod tests {
+    use super::*;
+    use crate::{
+        lang::Lang,
+        splitter::cache,
+    };
+
+    #[test]
+    fn test_recover_within_the_timeout() {
+        let code = "fn a() {\n    run();\n}\n\nfn broken( {\n    let x = ;\n}\n";
+        let lang_config = Lang::from_name("Rust").unwrap();
+        let tree = cache::parse(lang_config, code).unwrap();
+        let recovered = recover(lang_config, &tree, code, None).unwrap();
+        assert!(!recovered.masked_line_ranges.is_empty());
+        // the budget is spent before the first reparse
+        let recovered = recover(lang_config, &tree, code, Some(Duration::ZERO)).unwrap();
+        assert!(recovered.masked_line_ranges.is_empty());
+        assert_eq!(recovered.code, code);
+    }
+}
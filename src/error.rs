@@ -0,0 +1,142 @@
+//
+// error.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use crate::Limit;
+use std::fmt;
+pub use tree_sitter::QueryErrorKind;
+
+/// The errors returned by the splitter
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SplitError {
+    /// no language is registered for the file
+    UnsupportedLanguage {
+        /// the name of the file
+        filename: String,
+    },
+    /// the grammar of the language can't be loaded by the parser, usually because it
+    /// was generated by an incompatible version of tree-sitter
+    IncompatibleGrammar {
+        /// the name of the language
+        lang: String,
+    },
+    /// the parser didn't return a syntax tree
+    ParseFailed,
+    /// the query of the language doesn't compile
+    QueryError {
+        /// the name of the language
+        lang: String,
+        /// the byte offset of the error in the query
+        offset: usize,
+        /// the kind of the error
+        kind: QueryErrorKind,
+        /// the message of the query compiler
+        message: String,
+    },
+    /// the captures of the query don't describe a supported entity
+    InvalidEntity {
+        /// the reason the entity is rejected
+        reason: String,
+    },
+    /// the options can't be used to split the code
+    InvalidOptions {
+        /// the reason the options are rejected
+        reason: String,
+    },
+    /// the parser didn't finish in the configured time
+    Timeout,
+    /// the file exceeds one of the configured limits
+    LimitExceeded {
+        /// the exceeded limit
+        limit: Limit,
+    },
+    /// the code is not valid UTF-8
+    InvalidUtf8(std::str::Utf8Error),
+    /// the file can't be read
+    Io(std::io::Error),
+    /// the directory can't be walked, e.g. an include or exclude glob is invalid
+    Walk(ignore::Error),
+    /// a line of the unified diff can't be parsed
+    InvalidDiff {
+        /// the invalid line
+        line: String,
+    },
+    /// the file is not a valid Jupyter notebook
+    InvalidNotebook {
+        /// the reason the notebook is rejected
+        reason: String,
+    },
+    /// the splitter panicked while splitting the file
+    Panicked,
+}
+
+/// The result type of the splitter
+pub type Result<T> = std::result::Result<T, SplitError>;
+
+impl fmt::Display for SplitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitError::UnsupportedLanguage { filename } => {
+                write!(f, "Unsupported language for {}", filename)
+            }
+            SplitError::IncompatibleGrammar { lang } => {
+                write!(f, "The grammar of {} is incompatible with the parser", lang)
+            }
+            SplitError::ParseFailed => write!(f, "Failed to parse code"),
+            SplitError::QueryError {
+                lang,
+                offset,
+                kind,
+                message,
+            } => write!(
+                f,
+                "Invalid {} query at offset {}: {:?} error, {}",
+                lang, offset, kind, message
+            ),
+            SplitError::InvalidEntity { reason } => write!(f, "Invalid entity: {}", reason),
+            SplitError::InvalidOptions { reason } => write!(f, "Invalid options: {}", reason),
+            SplitError::Timeout => write!(f, "Timed out while parsing code"),
+            SplitError::LimitExceeded { limit } => {
+                write!(f, "The file exceeds the {:?} limit", limit)
+            }
+            SplitError::InvalidUtf8(e) => write!(f, "Invalid UTF-8 code: {}", e),
+            SplitError::Io(e) => write!(f, "Failed to read the file: {}", e),
+            SplitError::Walk(e) => write!(f, "Failed to walk the directory: {}", e),
+            SplitError::InvalidDiff { line } => write!(f, "Invalid diff line: {}", line),
+            SplitError::InvalidNotebook { reason } => write!(f, "Invalid notebook: {}", reason),
+            SplitError::Panicked => write!(f, "Panicked while splitting the file"),
+        }
+    }
+}
+
+impl std::error::Error for SplitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SplitError::InvalidUtf8(e) => Some(e),
+            SplitError::Io(e) => Some(e),
+            SplitError::Walk(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SplitError {
+    fn from(e: std::io::Error) -> Self {
+        SplitError::Io(e)
+    }
+}
+
+impl From<std::str::Utf8Error> for SplitError {
+    fn from(e: std::str::Utf8Error) -> Self {
+        SplitError::InvalidUtf8(e)
+    }
+}
+
+impl From<ignore::Error> for SplitError {
+    fn from(e: ignore::Error) -> Self {
+        SplitError::Walk(e)
+    }
+}
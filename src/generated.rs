@@ -0,0 +1,237 @@
+//
+// generated.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::path::Path;
+
+/// the number of lines at the top of the file searched for a marker
+const HEADER_LINES: usize = 20;
+/// the average length of the non-blank lines above which a file is minified
+const MINIFIED_AVERAGE_LINE_LENGTH: usize = 200;
+/// the minimum size in bytes of a minified file, a short file is never minified
+const MINIFIED_MIN_BYTES: usize = 1024;
+
+/// the markers put in a line at the top of a generated file, `@generated` is used by
+/// Facebook tools, prost or Cargo, `<auto-generated>` by the .NET generators and the
+/// last one by the `@Generated` annotation of the gRPC Java stubs
+const GENERATED_MARKERS: &[&str] = &["@generated", "<auto-generated>", "by gRPC proto compiler"];
+/// the comment a Go generator puts at the top of its files, see
+/// <https://go.dev/s/generatedcode>, it matches `^// Code generated .* DO NOT EDIT\.$`
+const GO_GENERATED_PREFIX: &str = "// Code generated ";
+const GO_GENERATED_SUFFIX: &str = " DO NOT EDIT.";
+/// the prefixes of the comment lines searched for the comments of the generators
+const COMMENT_PREFIXES: &[&str] = &["<!--", "//", "/*", "--", "#", "*", ";", "%"];
+/// the start of the comment written by many generators, e.g. protoc with `Generated by
+/// the protocol buffer compiler.  DO NOT EDIT!`, the gRPC plugins or Django migrations
+const GENERATED_BY: &str = "Generated by ";
+/// the warning of a generator, only followed by punctuation or the end of the comment
+const DO_NOT_EDIT: &str = "DO NOT EDIT";
+
+/// the file name suffixes of the files written by the common generators and minifiers
+const GENERATED_SUFFIXES: &[(&str, GeneratedKind)] = &[
+    (".min.js", GeneratedKind::Minified),
+    (".min.css", GeneratedKind::Minified),
+    (".min.mjs", GeneratedKind::Minified),
+    (".bundle.js", GeneratedKind::Minified),
+    (".pb.go", GeneratedKind::Generated),
+    (".pb.cc", GeneratedKind::Generated),
+    (".pb.h", GeneratedKind::Generated),
+    ("_pb2.py", GeneratedKind::Generated),
+    ("_pb2_grpc.py", GeneratedKind::Generated),
+    ("_pb.js", GeneratedKind::Generated),
+    ("_pb.d.ts", GeneratedKind::Generated),
+    (".g.dart", GeneratedKind::Generated),
+    (".generated.cs", GeneratedKind::Generated),
+    (".designer.cs", GeneratedKind::Generated),
+];
+
+/// The kind of a file not written by hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GeneratedKind {
+    /// the code is minified, e.g. a JavaScript bundle
+    Minified,
+    /// the code is written by a generator, e.g. protobuf or gRPC stubs
+    Generated,
+}
+
+/// Detects a minified or generated file
+///
+/// A file is generated if its name has the suffix of a known generator, e.g.
+/// `_pb2.py`, or if its first lines carry the marker of a generator: `@generated`,
+/// `<auto-generated>`, the `// Code generated ... DO NOT EDIT.` line of Go, a comment
+/// starting with `Generated by`, as written by protoc and gRPC, or a comment ending with
+/// `DO NOT EDIT`. A file is minified if its name has the suffix of a minifier, e.g.
+/// `.min.js`, or if its lines are very long on average.
+///
+/// # Example
+///
+/// ```
+/// use devgen_splitter::{
+///     detect_generated,
+///     GeneratedKind,
+/// };
+///
+/// let code = "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage pb\n";
+/// assert_eq!(
+///     detect_generated("service.go", code),
+///     Some(GeneratedKind::Generated)
+/// );
+/// assert_eq!(detect_generated("main.go", "package main\n"), None);
+/// ```
+pub fn detect_generated(filename: &str, code: &str) -> Option<GeneratedKind> {
+    let name = Path::new(filename)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(filename)
+        .to_lowercase();
+    if let Some((_, kind)) = GENERATED_SUFFIXES
+        .iter()
+        .find(|(suffix, _)| name.ends_with(suffix))
+    {
+        return Some(*kind);
+    }
+    let has_marker = code.lines().take(HEADER_LINES).any(|line| {
+        let line = line.trim_end();
+        GENERATED_MARKERS.iter().any(|marker| line.contains(marker))
+            || (line.len() >= GO_GENERATED_PREFIX.len() + GO_GENERATED_SUFFIX.len()
+                && line.starts_with(GO_GENERATED_PREFIX)
+                && line.ends_with(GO_GENERATED_SUFFIX))
+            || comment(line).is_some_and(|comment| {
+                comment.starts_with(GENERATED_BY)
+                    || comment.find(DO_NOT_EDIT).is_some_and(|i| {
+                        comment[i + DO_NOT_EDIT.len()..]
+                            .chars()
+                            .all(|c| c.is_whitespace() || ".!:*/->".contains(c))
+                    })
+            })
+    });
+    if has_marker {
+        return Some(GeneratedKind::Generated);
+    }
+    if code.len() >= MINIFIED_MIN_BYTES {
+        let (count, length) = code
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .fold((0, 0), |(count, length), line| {
+                (count + 1, length + line.len())
+            });
+        if count > 0 && length / count > MINIFIED_AVERAGE_LINE_LENGTH {
+            return Some(GeneratedKind::Minified);
+        }
+    }
+    None
+}
+
+/// Returns the text of a comment line without its comment markers, `None` if the line
+/// is not a comment
+fn comment(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let prefix = COMMENT_PREFIXES
+        .iter()
+        .find(|prefix| line.starts_with(*prefix))?;
+    Some(
+        line[prefix.len()..].trim_start_matches(|c: char| c.is_whitespace() || "/#*!-".contains(c)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("app.min.js", "var a=1;", Some(GeneratedKind::Minified))]
+    #[case("src/service_pb2.py", "import grpc\n", Some(GeneratedKind::Generated))]
+    #[case(
+        "api.rs",
+        "// This file is @generated by prost-build.\npub mod api {}\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "Api.cs",
+        "// <auto-generated>\n//     This code was generated by a tool.\n// </auto-generated>\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "api.go",
+        "// Code generated by protoc-gen-go. DO NOT EDIT.\r\npackage api\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "Api.java",
+        "// Generated by the protocol buffer compiler.  DO NOT EDIT!\n// source: api.proto\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "api.cc",
+        "// Generated by the protocol buffer compiler.  DO NOT EDIT!\n#include \"api.h\"\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "api.py",
+        "# -*- coding: utf-8 -*-\n# Generated by the protocol buffer compiler.  DO NOT EDIT!\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "api_grpc.py",
+        "# Generated by the gRPC Python protocol compiler plugin. DO NOT EDIT!\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "api.grpc.cc",
+        "// Generated by the gRPC C++ plugin.\n// If you make any local change, they will be lost.\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "ApiGrpc.java",
+        "package api;\n\n@javax.annotation.Generated(\n    value = \"by gRPC proto compiler (version 1.58.0)\",\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "0001_initial.py",
+        "# Generated by Django 4.2 on 2024-01-01 00:00\n\nfrom django.db import migrations\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "schema.sql",
+        "-- This file is written by sqlc.\n-- DO NOT EDIT.\nCREATE TABLE users (id INT);\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case(
+        "types.h",
+        "/*\n * Written by bindgen, DO NOT EDIT\n */\n",
+        Some(GeneratedKind::Generated)
+    )]
+    #[case("main.rs", "fn main() {}\n", None)]
+    #[case("main.py", "WARNING = \"DO NOT EDIT\"\n", None)]
+    #[case("main.py", "# The parser is generated by hand\n", None)]
+    #[case("notes.md", "# Do not edit this section\n", None)]
+    #[case("config.ts", "// auto-generated defaults, edit them freely\n", None)]
+    #[case("main.go", "// Code generated by hand, DO NOT EDIT the tests.\n", None)]
+    fn test_detect_generated(
+        #[case] filename: &str,
+        #[case] code: &str,
+        #[case] expected: Option<GeneratedKind>,
+    ) {
+        assert_eq!(detect_generated(filename, code), expected);
+    }
+
+    #[test]
+    fn test_detect_minified() {
+        let line = "var a=function(b){return b+1};".repeat(20);
+        let code = format!("{}\n{}\n", line, line);
+        assert_eq!(
+            detect_generated("bundle.js", &code),
+            Some(GeneratedKind::Minified)
+        );
+        let code = "fn main() {}\n".repeat(200);
+        assert_eq!(detect_generated("main.rs", &code), None);
+    }
+}
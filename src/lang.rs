@@ -4,8 +4,12 @@
 // Distributed under terms of the MIT license.
 //
 
+mod detect;
 mod queries;
-use queries::ALL_LANGS;
+mod registry;
+
+use crate::Result;
+pub(crate) use queries::EMPTY_QUERY;
 
 /// the language config
 #[derive(Debug)]
@@ -18,6 +22,93 @@ pub struct LangConfig {
     pub file_extensions: &'static [&'static str],
     /// the query used to extract the class, function definition
     pub query: &'static str,
+    /// the query capturing the references to other entities, e.g. `@reference.call`,
+    /// `@reference.type` and `@reference.module`. Empty if the language has none
+    pub reference_query: &'static str,
+    /// the query capturing the imports as `@import.statement` and the names they bind
+    /// as `@import.name`, or as `@import.path` for a Go package. Empty if the language
+    /// has none
+    pub import_query: &'static str,
+}
+
+impl LangConfig {
+    /// Creates the config of a language not bundled with the splitter
+    ///
+    /// The strings are leaked to live as long as the bundled configs, so the configs
+    /// are meant to be created once, e.g. at startup.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the language, e.g. `"HCL"`.
+    /// * `grammar` - The tree-sitter grammar, e.g. `tree_sitter_hcl::language`.
+    /// * `file_extensions` - The extensions of the files, without the dot.
+    /// * `query` - The query capturing the entities, following the capture names of the bundled
+    ///   queries such as `@function.definition` and `@function.name`. An empty query splits the
+    ///   files by their syntax nodes only.
+    pub fn new(
+        name: &str,
+        grammar: fn() -> tree_sitter::Language,
+        file_extensions: &[&str],
+        query: &str,
+    ) -> Self {
+        Self {
+            lang: registry::leak_strs(&[name]),
+            grammar,
+            file_extensions: registry::leak_strs(file_extensions),
+            query: registry::leak_str(query),
+            reference_query: EMPTY_QUERY,
+            import_query: EMPTY_QUERY,
+        }
+    }
+
+    /// Returns a copy of the config using the query, e.g. to replace the bundled query
+    /// of a language
+    ///
+    /// Like `new`, the strings are leaked, so the copies are meant to be created once.
+    pub fn with_query(&self, query: &str) -> Self {
+        Self {
+            query: registry::leak_str(query),
+            ..*self
+        }
+    }
+
+    /// Returns a copy of the config using the reference query, see
+    /// `SplitOptions::collect_references`
+    ///
+    /// Like `new`, the strings are leaked, so the copies are meant to be created once.
+    pub fn with_reference_query(&self, reference_query: &str) -> Self {
+        Self {
+            reference_query: registry::leak_str(reference_query),
+            ..*self
+        }
+    }
+
+    /// Returns a copy of the config using the import query, see
+    /// `SplitOptions::collect_imports`
+    ///
+    /// Like `new`, the strings are leaked, so the copies are meant to be created once.
+    pub fn with_import_query(&self, import_query: &str) -> Self {
+        Self {
+            import_query: registry::leak_str(import_query),
+            ..*self
+        }
+    }
+
+    /// Returns a copy of the config mapping the extensions to the language instead of
+    /// its own extensions
+    ///
+    /// Like `new`, the strings are leaked, so the copies are meant to be created once.
+    pub fn with_file_extensions(&self, file_extensions: &[&str]) -> Self {
+        Self {
+            file_extensions: registry::leak_strs(file_extensions),
+            ..*self
+        }
+    }
+
+    /// Returns the main name of the language, e.g. `"Rust"`
+    pub fn name(&self) -> &'static str {
+        self.lang.first().copied().unwrap_or("")
+    }
 }
 
 pub struct Lang;
@@ -53,17 +144,100 @@ impl Lang {
     pub fn from_filename(filename: &str) -> Option<&'static LangConfig> {
         let path = std::path::Path::new(filename);
         let file_ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-        let lang = ALL_LANGS
+        registry::all_langs()
             .iter()
-            .find(|l| l.file_extensions.iter().any(|&ext| ext == file_ext));
-        match lang {
-            Some(lang) => Some(lang),
-            None => None,
-        }
+            .find(|l| l.file_extensions.contains(&file_ext))
+            .copied()
+    }
+
+    /// Determines the language configuration based on the filename and the content of
+    /// the file.
+    ///
+    /// The content is consulted before the extension, in this order:
+    ///
+    /// 1. a Vim or Emacs modeline in the first or the last lines, e.g. `# vim: ft=python`
+    /// 2. the interpreter of a shebang, e.g. `#!/usr/bin/env bash`
+    /// 3. a well-known file name without a supported extension, e.g. `Gemfile`
+    /// 4. for a `.h` header without a language registered for it, C++ constructs such as
+    ///    `namespace` or `template` choose C++ over C
+    /// 5. the extension, as in `from_filename`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use devgen_splitter::Lang;
+    /// let lang_config = Lang::detect("bin/deploy", "#!/usr/bin/env bash\necho deploy\n");
+    /// assert_eq!(lang_config.unwrap().lang[0], "Bash");
+    /// let lang_config = Lang::detect("vector.h", "namespace util {\nclass Vector;\n}\n");
+    /// assert_eq!(lang_config.unwrap().lang[0], "C++");
+    /// ```
+    pub fn detect(filename: &str, code: &str) -> Option<&'static LangConfig> {
+        detect::detect(filename, code)
+    }
+
+    /// Determines the language configuration based on a language name or a file extension.
+    ///
+    /// The name is matched case-insensitively, e.g. `"rust"`, `"TypeScript"` or `"py"`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use devgen_splitter::Lang;
+    /// assert_eq!(Lang::from_name("rust").unwrap().lang[0], "Rust");
+    /// assert_eq!(Lang::from_name("py").unwrap().lang[0], "Python");
+    /// ```
+    pub fn from_name(name: &str) -> Option<&'static LangConfig> {
+        let langs = registry::all_langs();
+        langs
+            .iter()
+            .find(|l| l.lang.iter().any(|lang| lang.eq_ignore_ascii_case(name)))
+            .or_else(|| langs.iter().find(|l| l.file_extensions.contains(&name)))
+            .copied()
+    }
+
+    /// Registers a language at runtime, in place of the registered or bundled
+    /// language of the same name
+    ///
+    /// The registered languages come before the bundled ones in `from_filename`,
+    /// `from_name` and `detect`, so they're used by `split` and `Splitter` too. A
+    /// bundled language is replaced as a whole: its bundled query and extensions are no
+    /// longer used.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SplitError::IncompatibleGrammar` if the grammar can't be loaded by the
+    /// parser, or `SplitError::QueryError` if the query doesn't compile.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use devgen_splitter::{
+    ///     Lang,
+    ///     LangConfig,
+    /// };
+    ///
+    /// // parse the `.rhai` scripts with the Rust grammar
+    /// let rust = Lang::from_name("rust").unwrap();
+    /// let config = LangConfig::new("Rhai", rust.grammar, &["rhai"], rust.query);
+    /// Lang::register(config).unwrap();
+    /// assert_eq!(Lang::from_filename("init.rhai").unwrap().lang[0], "Rhai");
+    /// ```
+    pub fn register(lang_config: LangConfig) -> Result<&'static LangConfig> {
+        registry::register(lang_config)
+    }
+
+    /// Returns the registered languages and the bundled languages they don't replace
+    pub fn all() -> Vec<&'static LangConfig> {
+        registry::all_langs().to_vec()
     }
 }
 
-#[cfg(test)]
+#[cfg(all(
+    test,
+    feature = "lang-rust",
+    feature = "lang-typescript",
+    feature = "lang-csharp"
+))]
 mod tests {
     use super::*;
     use rstest::rstest;
@@ -82,4 +256,14 @@ mod tests {
             None => assert!(result.is_none()),
         }
     }
+
+    #[rstest]
+    #[case("rust", Some("Rust"))]
+    #[case("C#", Some("C#"))]
+    #[case("ts", Some("TypeScript"))]
+    #[case("unknown", None)]
+    fn test_from_name(#[case] name: &str, #[case] expected_lang: Option<&str>) {
+        let result = Lang::from_name(name);
+        assert_eq!(result.map(|config| config.lang[0]), expected_lang);
+    }
 }
@@ -0,0 +1,222 @@
+//
+// detect.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    registry,
+    Lang,
+    LangConfig,
+};
+use std::path::Path;
+
+/// the number of lines at the top and at the bottom of the file searched for a modeline
+const MODELINE_LINES: usize = 5;
+/// the number of lines of a header searched for a C++ construct
+const HEADER_LINES: usize = 200;
+
+/// the file names of the files without a supported extension, e.g. `Gemfile`
+const KNOWN_FILENAMES: &[(&str, &str)] = &[
+    (".bashrc", "Bash"),
+    (".bash_profile", "Bash"),
+    (".bash_logout", "Bash"),
+    (".profile", "Bash"),
+    (".zshrc", "Bash"),
+    (".zprofile", "Bash"),
+    ("PKGBUILD", "Bash"),
+    ("APKBUILD", "Bash"),
+    ("Gemfile", "Ruby"),
+    ("Rakefile", "Ruby"),
+    ("Podfile", "Ruby"),
+    ("Fastfile", "Ruby"),
+    ("Vagrantfile", "Ruby"),
+    ("Brewfile", "Ruby"),
+    ("Guardfile", "Ruby"),
+    ("SConstruct", "Python"),
+    ("SConscript", "Python"),
+    ("Pipfile", "TOML"),
+    ("Cargo.lock", "TOML"),
+];
+
+/// the names used by the shebangs and the modelines for the supported languages
+const ALIASES: &[(&str, &str)] = &[
+    ("sh", "Bash"),
+    ("zsh", "Bash"),
+    ("ksh", "Bash"),
+    ("dash", "Bash"),
+    ("ash", "Bash"),
+    ("shell-script", "Bash"),
+    ("node", "JavaScript"),
+    ("nodejs", "JavaScript"),
+    ("javascript", "JavaScript"),
+    ("js2", "JavaScript"),
+    ("deno", "TypeScript"),
+    ("ts-node", "TypeScript"),
+    ("typescriptreact", "TypeScript"),
+    ("pypy", "Python"),
+    ("rscript", "R"),
+    ("c++", "C++"),
+    ("cpp", "C++"),
+    ("csharp", "C#"),
+    ("golang", "Go"),
+];
+
+/// Detects the language from the content of the file, then from its name
+pub(crate) fn detect(filename: &str, code: &str) -> Option<&'static LangConfig> {
+    if let Some(lang_config) = modeline(code).and_then(from_alias) {
+        return Some(lang_config);
+    }
+    if let Some(lang_config) = shebang(code).and_then(from_alias) {
+        return Some(lang_config);
+    }
+    let path = Path::new(filename);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    if let Some((_, lang)) = KNOWN_FILENAMES.iter().find(|(known, _)| *known == name) {
+        return Lang::from_name(lang);
+    }
+    let file_ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    // a language registered for the headers, e.g. Objective-C, wins over the heuristic,
+    // a registered C or C++ is found by the heuristic
+    let registered = registry::registered_for_extension(file_ext)
+        .filter(|lang_config| !["C", "C++"].contains(&lang_config.name()));
+    if file_ext == "h" && registered.is_none() {
+        let lang = if is_cpp_header(code) { "C++" } else { "C" };
+        return Lang::from_name(lang);
+    }
+    Lang::from_filename(filename)
+}
+
+/// Finds a language by a name of a shebang or a modeline, the version suffix of an
+/// interpreter such as `python3.11` is ignored
+fn from_alias(name: &str) -> Option<&'static LangConfig> {
+    let name = name.to_lowercase();
+    let name = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let lang = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, lang)| *lang)
+        .unwrap_or(name);
+    Lang::from_name(lang)
+}
+
+/// Returns the interpreter of the shebang, e.g. `python3` for
+/// `#!/usr/bin/env -S python3 -u`
+fn shebang(code: &str) -> Option<&str> {
+    let line = code.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    Some(program)
+}
+
+/// Returns the file type of a Vim modeline (`vim: set ft=python:`) or the mode of an
+/// Emacs modeline (`-*- mode: ruby -*-` or `-*- ruby -*-`)
+fn modeline(code: &str) -> Option<&str> {
+    let lines = code.lines().collect::<Vec<&str>>();
+    let tail = lines
+        .len()
+        .saturating_sub(MODELINE_LINES)
+        .max(MODELINE_LINES);
+    lines
+        .iter()
+        .take(MODELINE_LINES)
+        .chain(lines.iter().skip(tail))
+        .find_map(|line| vim_modeline(line).or_else(|| emacs_modeline(line)))
+}
+
+fn vim_modeline(line: &str) -> Option<&str> {
+    let options = ["vim:", "vi:", "ex:"].iter().find_map(|prefix| {
+        line.match_indices(prefix)
+            .find(|(i, _)| {
+                line[..*i]
+                    .chars()
+                    .next_back()
+                    .is_none_or(char::is_whitespace)
+            })
+            .map(|(i, _)| &line[i + prefix.len()..])
+    })?;
+    options
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .find_map(|option| {
+            option
+                .strip_prefix("ft=")
+                .or_else(|| option.strip_prefix("filetype="))
+                .or_else(|| option.strip_prefix("syntax="))
+        })
+        .filter(|name| !name.is_empty())
+}
+
+fn emacs_modeline(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (variables, _) = rest.split_once("-*-")?;
+    let variables = variables.trim();
+    if !variables.contains(':') {
+        return Some(variables).filter(|name| !name.is_empty());
+    }
+    variables.split(';').find_map(|variable| {
+        let (key, value) = variable.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case("mode")
+            .then(|| value.trim())
+    })
+}
+
+/// Returns true if the header uses a construct of C++ that doesn't exist in C
+fn is_cpp_header(code: &str) -> bool {
+    code.lines().take(HEADER_LINES).any(|line| {
+        let line = line.trim();
+        line.starts_with("namespace ")
+            || line.starts_with("class ")
+            || line.starts_with("template")
+            || line.starts_with("using ")
+            || line.starts_with("public:")
+            || line.starts_with("private:")
+            || line.starts_with("protected:")
+            || line.contains("std::")
+            // the standard C++ headers have no extension, e.g. `<vector>`
+            || line
+                .strip_prefix("#include <")
+                .is_some_and(|header| !header.contains('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("#!/bin/bash\necho hi\n", Some("bash"))]
+    #[case("#!/usr/bin/env python3\n", Some("python3"))]
+    #[case("#!/usr/bin/env -S node --harmony\n", Some("node"))]
+    #[case("#! /usr/bin/ruby -w\n", Some("ruby"))]
+    #[case("echo hi\n", None)]
+    fn test_shebang(#[case] code: &str, #[case] expected: Option<&str>) {
+        assert_eq!(shebang(code), expected);
+    }
+
+    #[rstest]
+    #[case("# vim: set ft=python:\n", Some("python"))]
+    #[case("x = 1\n// vim: filetype=cpp ts=4\n", Some("cpp"))]
+    #[case("# -*- mode: ruby; coding: utf-8 -*-\n", Some("ruby"))]
+    #[case("/* -*- c++ -*- */\n", Some("c++"))]
+    #[case("let vim = 1;\n", None)]
+    fn test_modeline(#[case] code: &str, #[case] expected: Option<&str>) {
+        assert_eq!(modeline(code), expected);
+    }
+
+    #[rstest]
+    #[case("#include <vector>\nint f();\n", true)]
+    #[case("namespace a {\nint f();\n}\n", true)]
+    #[case("#include <stdio.h>\nint f(void);\n", false)]
+    #[case("#ifdef __cplusplus\nextern \"C\" {\n#endif\nint f(void);\n", false)]
+    fn test_is_cpp_header(#[case] code: &str, #[case] expected: bool) {
+        assert_eq!(is_cpp_header(code), expected);
+    }
+}
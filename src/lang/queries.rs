@@ -6,164 +6,269 @@
 
 use super::LangConfig;
 
-const RUST_QUERY: &'static str = include_str!("../../queries/rust.scm");
-const TYPESCRIPT_QUERY: &'static str = include_str!("../../queries/typescript.scm");
-const JAVA_QUERY: &'static str = include_str!("../../queries/java.scm");
-const PYTHON_QUERY: &'static str = include_str!("../../queries/python.scm");
-const SOLIDITY_QUERY: &'static str = include_str!("../../queries/solidity.scm");
+#[cfg(feature = "lang-rust")]
+const RUST_QUERY: &str = include_str!("../../queries/rust.scm");
+#[cfg(feature = "lang-typescript")]
+const TYPESCRIPT_QUERY: &str = include_str!("../../queries/typescript.scm");
+#[cfg(feature = "lang-java")]
+const JAVA_QUERY: &str = include_str!("../../queries/java.scm");
+#[cfg(feature = "lang-python")]
+const PYTHON_QUERY: &str = include_str!("../../queries/python.scm");
+#[cfg(feature = "lang-solidity")]
+const SOLIDITY_QUERY: &str = include_str!("../../queries/solidity.scm");
+#[cfg(feature = "lang-rust")]
+const RUST_REFERENCE_QUERY: &str = include_str!("../../queries/references/rust.scm");
+#[cfg(feature = "lang-typescript")]
+const TYPESCRIPT_REFERENCE_QUERY: &str = include_str!("../../queries/references/typescript.scm");
+#[cfg(feature = "lang-java")]
+const JAVA_REFERENCE_QUERY: &str = include_str!("../../queries/references/java.scm");
+#[cfg(feature = "lang-python")]
+const PYTHON_REFERENCE_QUERY: &str = include_str!("../../queries/references/python.scm");
+#[cfg(feature = "lang-javascript")]
+const JAVASCRIPT_REFERENCE_QUERY: &str = include_str!("../../queries/references/javascript.scm");
+#[cfg(feature = "lang-go")]
+const GO_REFERENCE_QUERY: &str = include_str!("../../queries/references/go.scm");
+#[cfg(feature = "lang-rust")]
+const RUST_IMPORT_QUERY: &str = include_str!("../../queries/imports/rust.scm");
+#[cfg(feature = "lang-typescript")]
+const TYPESCRIPT_IMPORT_QUERY: &str = include_str!("../../queries/imports/typescript.scm");
+#[cfg(feature = "lang-java")]
+const JAVA_IMPORT_QUERY: &str = include_str!("../../queries/imports/java.scm");
+#[cfg(feature = "lang-python")]
+const PYTHON_IMPORT_QUERY: &str = include_str!("../../queries/imports/python.scm");
+#[cfg(feature = "lang-javascript")]
+const JAVASCRIPT_IMPORT_QUERY: &str = include_str!("../../queries/imports/javascript.scm");
+#[cfg(feature = "lang-go")]
+const GO_IMPORT_QUERY: &str = include_str!("../../queries/imports/go.scm");
 // empty query means this language doesn't support context splitting
-const EMPTY_QUERY: &'static str = "";
+pub(crate) const EMPTY_QUERY: &str = "";
 
+#[cfg(feature = "lang-rust")]
 static RUST_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Rust"],
     grammar: tree_sitter_rust::language,
     file_extensions: &["rs"],
     query: RUST_QUERY,
+    reference_query: RUST_REFERENCE_QUERY,
+    import_query: RUST_IMPORT_QUERY,
 };
 
+#[cfg(feature = "lang-typescript")]
 static TYPESCRIPT_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["TypeScript"],
     grammar: tree_sitter_typescript::language_tsx,
     file_extensions: &["ts", "tsx"],
     query: TYPESCRIPT_QUERY,
+    reference_query: TYPESCRIPT_REFERENCE_QUERY,
+    import_query: TYPESCRIPT_IMPORT_QUERY,
 };
 
+#[cfg(feature = "lang-java")]
 static JAVA_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Java"],
     grammar: tree_sitter_java::language,
     file_extensions: &["java"],
     query: JAVA_QUERY,
+    reference_query: JAVA_REFERENCE_QUERY,
+    import_query: JAVA_IMPORT_QUERY,
 };
 
+#[cfg(feature = "lang-cpp")]
 static CPP_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["C++"],
     grammar: tree_sitter_cpp::language,
     file_extensions: &["cpp", "cc", "h"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-python")]
 static PYTHON_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Python"],
     grammar: tree_sitter_python::language,
     file_extensions: &["py"],
     query: PYTHON_QUERY,
+    reference_query: PYTHON_REFERENCE_QUERY,
+    import_query: PYTHON_IMPORT_QUERY,
 };
 
+#[cfg(feature = "lang-c")]
 static C_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["C"],
     grammar: tree_sitter_c::language,
     file_extensions: &["c", "h"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-javascript")]
 static JAVASCRIPT_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["JavaScript"],
     grammar: tree_sitter_javascript::language,
     file_extensions: &["js", "jsx"],
     query: EMPTY_QUERY,
+    reference_query: JAVASCRIPT_REFERENCE_QUERY,
+    import_query: JAVASCRIPT_IMPORT_QUERY,
 };
 
+#[cfg(feature = "lang-markdown")]
 static MARKDOWN_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Markdown"],
     grammar: tree_sitter_md::language,
     file_extensions: &["md"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-go")]
 static GO_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Go"],
     grammar: tree_sitter_go::language,
     file_extensions: &["go"],
     query: EMPTY_QUERY,
+    reference_query: GO_REFERENCE_QUERY,
+    import_query: GO_IMPORT_QUERY,
 };
 
+#[cfg(feature = "lang-solidity")]
 static SOLIDITY_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Solidity"],
     grammar: devgen_tree_sitter_solidity::language,
     file_extensions: &["sol"],
     query: SOLIDITY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-toml")]
 static TOML_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["TOML"],
     grammar: devgen_tree_sitter_toml::language,
     file_extensions: &["toml"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-protobuf")]
 static PROTOBUF_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Protobuf"],
     grammar: devgen_tree_sitter_protobuf::language,
     file_extensions: &["proto"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-r")]
 static R_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["R"],
     grammar: devgen_tree_sitter_r::language,
     file_extensions: &["r", "R"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-swift")]
 static SWIFT_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Swift"],
     grammar: devgen_tree_sitter_swift::language,
     file_extensions: &["swift"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-php")]
 static PHP_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["PHP"],
     grammar: devgen_tree_sitter_php::language_php,
     file_extensions: &["php"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-sql")]
 static SQL_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["SQL"],
     grammar: devgen_tree_sitter_sql::language,
     file_extensions: &["sql"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-bash")]
 static BASH_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Bash"],
     grammar: tree_sitter_bash::language,
     file_extensions: &["sh"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-csharp")]
 static C_SHARP_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["C#"],
     grammar: tree_sitter_c_sharp::language,
     file_extensions: &["cs"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 
+#[cfg(feature = "lang-ruby")]
 static RUBY_LANG_CONFIG: LangConfig = LangConfig {
     lang: &["Ruby"],
     grammar: tree_sitter_ruby::language,
     file_extensions: &["rb"],
     query: EMPTY_QUERY,
+    reference_query: EMPTY_QUERY,
+    import_query: EMPTY_QUERY,
 };
 pub static ALL_LANGS: &[&LangConfig] = &[
+    #[cfg(feature = "lang-rust")]
     &RUST_LANG_CONFIG,
+    #[cfg(feature = "lang-typescript")]
     &TYPESCRIPT_LANG_CONFIG,
+    #[cfg(feature = "lang-java")]
     &JAVA_LANG_CONFIG,
+    #[cfg(feature = "lang-python")]
     &PYTHON_LANG_CONFIG,
+    #[cfg(feature = "lang-c")]
     &C_LANG_CONFIG,
+    #[cfg(feature = "lang-javascript")]
     &JAVASCRIPT_LANG_CONFIG,
+    #[cfg(feature = "lang-markdown")]
     &MARKDOWN_LANG_CONFIG,
+    #[cfg(feature = "lang-cpp")]
     &CPP_LANG_CONFIG,
+    #[cfg(feature = "lang-go")]
     &GO_LANG_CONFIG,
+    #[cfg(feature = "lang-solidity")]
     &SOLIDITY_LANG_CONFIG,
+    #[cfg(feature = "lang-toml")]
     &TOML_LANG_CONFIG,
+    #[cfg(feature = "lang-protobuf")]
     &PROTOBUF_LANG_CONFIG,
+    #[cfg(feature = "lang-r")]
     &R_LANG_CONFIG,
+    #[cfg(feature = "lang-swift")]
     &SWIFT_LANG_CONFIG,
+    #[cfg(feature = "lang-sql")]
     &SQL_LANG_CONFIG,
+    #[cfg(feature = "lang-php")]
     &PHP_LANG_CONFIG,
+    #[cfg(feature = "lang-bash")]
     &BASH_LANG_CONFIG,
+    #[cfg(feature = "lang-csharp")]
     &C_SHARP_LANG_CONFIG,
+    #[cfg(feature = "lang-ruby")]
     &RUBY_LANG_CONFIG,
 ];
@@ -0,0 +1,119 @@
+//
+// registry.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    queries::{
+        ALL_LANGS,
+        EMPTY_QUERY,
+    },
+    LangConfig,
+};
+use crate::{
+    Result,
+    SplitError,
+};
+use std::sync::{
+    Arc,
+    RwLock,
+};
+use tree_sitter::{
+    Parser,
+    Query,
+};
+
+/// the languages registered at runtime, the latest first
+static REGISTERED_LANGS: RwLock<Vec<&'static LangConfig>> = RwLock::new(Vec::new());
+/// the registered and the bundled languages, built on the first lookup and replaced
+/// on every registration, so a lookup only clones the `Arc`
+static LANGS_SNAPSHOT: RwLock<Option<Arc<Vec<&'static LangConfig>>>> = RwLock::new(None);
+
+/// Returns the registered languages, then the bundled languages not replaced by a
+/// registered language of the same name
+pub(crate) fn all_langs() -> Arc<Vec<&'static LangConfig>> {
+    if let Some(langs) = LANGS_SNAPSHOT
+        .read()
+        .expect("the language registry is poisoned")
+        .as_ref()
+    {
+        return langs.clone();
+    }
+    let registered = REGISTERED_LANGS
+        .read()
+        .expect("the language registry is poisoned");
+    let mut snapshot = LANGS_SNAPSHOT
+        .write()
+        .expect("the language registry is poisoned");
+    snapshot
+        .get_or_insert_with(|| snapshot_of(&registered))
+        .clone()
+}
+
+/// Returns the latest language registered at runtime for the extension, the bundled
+/// languages are left out
+pub(crate) fn registered_for_extension(file_ext: &str) -> Option<&'static LangConfig> {
+    REGISTERED_LANGS
+        .read()
+        .expect("the language registry is poisoned")
+        .iter()
+        .find(|lang| lang.file_extensions.contains(&file_ext))
+        .copied()
+}
+
+/// Lists the registered languages, then the bundled languages they don't replace
+fn snapshot_of(registered: &[&'static LangConfig]) -> Arc<Vec<&'static LangConfig>> {
+    let mut langs = registered.to_vec();
+    let bundled = ALL_LANGS
+        .iter()
+        .filter(|bundled| !langs.iter().any(|lang| lang.name() == bundled.name()))
+        .copied()
+        .collect::<Vec<&'static LangConfig>>();
+    langs.extend(bundled);
+    Arc::new(langs)
+}
+
+/// Checks the grammar and the query of the config, then registers it in place of
+/// the language of the same name
+pub(crate) fn register(lang_config: LangConfig) -> Result<&'static LangConfig> {
+    let grammar = (lang_config.grammar)();
+    Parser::new()
+        .set_language(&grammar)
+        .map_err(|_| SplitError::IncompatibleGrammar {
+            lang: lang_config.name().to_string(),
+        })?;
+    if lang_config.query != EMPTY_QUERY {
+        Query::new(&grammar, lang_config.query).map_err(|e| SplitError::QueryError {
+            lang: lang_config.name().to_string(),
+            offset: e.offset,
+            kind: e.kind,
+            message: e.message,
+        })?;
+    }
+    let lang_config: &'static LangConfig = Box::leak(Box::new(lang_config));
+    let mut langs = REGISTERED_LANGS
+        .write()
+        .expect("the language registry is poisoned");
+    langs.retain(|lang| lang.name() != lang_config.name());
+    langs.insert(0, lang_config);
+    // the snapshot is replaced under the lock of the registered languages, so the
+    // registrations can't overwrite each other's snapshot
+    *LANGS_SNAPSHOT
+        .write()
+        .expect("the language registry is poisoned") = Some(snapshot_of(&langs));
+    Ok(lang_config)
+}
+
+/// Leaks the strings so they live as long as the bundled configs
+pub(crate) fn leak_strs(strs: &[&str]) -> &'static [&'static str] {
+    let strs = strs
+        .iter()
+        .map(|s| &*Box::leak(s.to_string().into_boxed_str()))
+        .collect::<Vec<&'static str>>();
+    Box::leak(strs.into_boxed_slice())
+}
+
+pub(crate) fn leak_str(s: &str) -> &'static str {
+    Box::leak(s.to_string().into_boxed_str())
+}
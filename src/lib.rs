@@ -11,8 +11,29 @@
 //! - `Entity`: Struct containing metadata about a specific code entity.
 //! - `Chunk`: Struct representing a section of code containing one or more entities.
 //! - `SplitOptions`: Configuration options for controlling how code is split into chunks.
-//! - `Lang`: Enum representing supported programming languages (imported from `lang` module).
+//! - `SplitError`: Enum of the errors returned by the splitter.
+//! - `Lang`: Enum representing supported programming languages (imported from `lang` module), with
+//!   a registry of the languages added or replaced at runtime.
 //! - `split`: Function for splitting code into chunks (imported from `splitter` module).
+//! - `split_with_diagnostics`: Function for splitting code and reporting its syntax errors, with an
+//!   option to recover the entities around them.
+//! - `Splitter`: Reusable splitter built with a builder, sharing its parsers and compiled queries
+//!   across threads.
+//! - `IncrementalSplitter`: Splitter for a file edited over time, reparsing incrementally and
+//!   reporting the chunks changed by an edit.
+//! - `Notebook`: Jupyter notebook, split cell by cell with the kernel language, with the `notebook`
+//!   feature.
+//! - `rechunk`: Function for re-chunking only the lines changed between two versions of a file,
+//!   from their contents or a unified diff.
+//! - `split_dir`: Function for splitting every supported file of a directory in parallel.
+//! - `detect_generated`: Function for detecting minified and generated files, handled by the
+//!   splitter according to `SplitOptions::generated`.
+//! - `Reference`: Struct representing a function, type or module used by a chunk, collected with
+//!   `SplitOptions::collect_references`.
+//! - `Import`: Struct representing an import used by a chunk, attached with
+//!   `SplitOptions::collect_imports`.
+//! - `validate_chunks`: Function for checking that chunks tile the whole file, and
+//!   `validate_notebook_chunks` for the cells of a notebook.
 //!
 //! ## Usage Example:
 //!
@@ -26,6 +47,7 @@
 //! let source_code = "// Your source code here...";
 //! let options = SplitOptions {
 //!     chunk_line_limit: 100,
+//!     ..Default::default()
 //! };
 //! let chunks = split("test.rs", source_code, &options).unwrap();
 //!
@@ -42,7 +64,10 @@ use serde::{
     Deserialize,
     Serialize,
 };
-use std::ops::Range;
+use std::{
+    ops::Range,
+    time::Duration,
+};
 
 /// Represents the different types of entities that can be identified in the code.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -57,6 +82,10 @@ pub enum EntityType {
     Method,
     /// Represents an enumeration definition
     Enum,
+    /// Represents a SQL query embedded in a string literal
+    Query,
+    /// Represents an import, e.g. a `use` declaration, named by its source text
+    Import,
 }
 
 /// Represents a code entity with its associated metadata.
@@ -74,12 +103,49 @@ pub struct Entity {
     pub completed_line_range: Range<usize>,
     /// the line range of the chunk in the current chunk
     pub chunk_line_range: Range<usize>,
-    /// if the entity is a method, the name of the parent struct or interface
+    /// if the entity is a method, the name of the parent struct or interface, if it is
+    /// an embedded query, the name of the entity enclosing it
     pub parent: Option<String>,
     /// the line range of the parent in the source code
     pub parent_line_range: Option<Range<usize>>,
 }
 
+/// The kind of a `Reference`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ReferenceKind {
+    /// a called function, method or macro, captured as `@reference.call`
+    Call,
+    /// a used or instantiated type, captured as `@reference.type`
+    Type,
+    /// an imported module, captured as `@reference.module`, a Rust use tree yields a
+    /// module for every path it imports
+    Module,
+}
+
+/// Represents an identifier used by a chunk and defined elsewhere, e.g. a called
+/// function, found by the reference query of the language.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reference {
+    /// the referenced name as written in the code, e.g. `push` for `items.push(1)`
+    pub name: String,
+    /// the kind of the reference
+    pub kind: ReferenceKind,
+    /// the line of the first use of the name in the chunk
+    pub line: usize,
+}
+
+/// Represents an import of the file used by a chunk, attached to the chunk as context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Import {
+    /// the source text of the import, e.g. `use std::collections::HashMap;`, or of the
+    /// import spec for a grouped Go import
+    pub statement: String,
+    /// the names bound by the import and used by the chunk
+    pub names: Vec<String>,
+    /// the line range of the import in the source code, the end line is included
+    pub line_range: Range<usize>,
+}
+
 /// Represents a chunk of code containing one or more entities.
 ///
 /// A chunk is a section of the source code that may contain multiple entities
@@ -90,6 +156,139 @@ pub struct Chunk {
     pub line_range: Range<usize>,
     /// The entities contained within this chunk
     pub entities: Vec<Entity>,
+    /// The stable id of the chunk, derived from the file path, the qualified path of
+    /// the entities and the content hash. It doesn't change when the chunk only moves,
+    /// identical chunks of a file are told apart by their occurrence
+    #[serde(default)]
+    pub id: String,
+    /// The hash of the chunk content, ignoring the whitespaces around every line and
+    /// the blank lines
+    #[serde(default)]
+    pub content_hash: String,
+    /// The chunk comes from a minified or generated file, only set with
+    /// `GeneratedPolicy::Tag`
+    #[serde(default)]
+    pub generated: bool,
+    /// The language of the chunk when it differs from the language of the file, e.g.
+    /// for a fenced code block of a Markdown file
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// The headings enclosing the chunk, outermost first, e.g. for a fenced code block
+    /// of a Markdown file
+    #[serde(default)]
+    pub headings: Vec<String>,
+    /// The notebook cell of the chunk, only set for a Jupyter notebook, with the lines
+    /// of the chunk in the cell. The line ranges of the chunk are the lines of the
+    /// notebook file holding them
+    #[serde(default)]
+    pub cell: Option<NotebookCell>,
+    /// The names referenced by the chunk, in the order of their first use, only set
+    /// with `SplitOptions::collect_references`
+    #[serde(default)]
+    pub references: Vec<Reference>,
+    /// The imports of the file outside the chunk whose names are used by the chunk, in
+    /// the order of the file, only set with `SplitOptions::collect_imports`
+    #[serde(default)]
+    pub imports: Vec<Import>,
+}
+
+impl Chunk {
+    /// Creates a chunk of the lines holding the entities, the other fields are left
+    /// empty, e.g. the id is set once the file is split
+    pub fn new(line_range: Range<usize>, entities: Vec<Entity>) -> Self {
+        Self {
+            line_range,
+            entities,
+            id: String::new(),
+            content_hash: String::new(),
+            generated: false,
+            lang: None,
+            headings: vec![],
+            cell: None,
+            references: vec![],
+            imports: vec![],
+        }
+    }
+}
+
+/// The amount of context shared between two consecutive chunks.
+///
+/// The overlap extends each chunk backward and forward, but the new edges are
+/// snapped to syntactic boundaries (the start or end of a statement or a child
+/// node), so the overlap never cuts a line of code in half.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChunkOverlap {
+    /// extend each chunk by at most this number of lines on each side
+    Lines(usize),
+    /// extend each chunk by at most this fraction of its own line count on each side
+    Ratio(f32),
+}
+
+/// The strategy used to choose where one chunk ends and the next one starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplitStrategy {
+    /// walk the syntax nodes and cut as soon as the chunk line limit is reached
+    #[default]
+    Greedy,
+    /// score every candidate cut point by its syntactic depth, blank lines and entity
+    /// boundaries, then pick the cut points that minimise the total cost with
+    /// dynamic programming. The chunk sizes are more even than with `Greedy`
+    Balanced,
+}
+
+/// A resource limit of `SplitLimits`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Limit {
+    /// the size of the file in bytes
+    FileBytes,
+    /// the length of the longest line in bytes
+    LineLength,
+    /// the time spent parsing the file
+    ParseTimeout,
+    /// the depth of the syntax tree
+    TreeDepth,
+}
+
+/// What to do when a file exceeds one of the `SplitLimits`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LimitAction {
+    /// return `SplitError::LimitExceeded`, or `SplitError::Timeout` for the parse timeout
+    #[default]
+    Error,
+    /// split the file into chunks of `chunk_line_limit` lines without entities
+    SplitByLines,
+}
+
+/// What to do with a minified or generated file, as detected by `detect_generated`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GeneratedPolicy {
+    /// split the file like any other file
+    #[default]
+    Ignore,
+    /// return no chunk for the file
+    Skip,
+    /// split the file into chunks of `chunk_line_limit` lines without parsing it
+    Coarse,
+    /// split the file like any other file and mark its chunks as generated
+    Tag,
+}
+
+/// The limits bounding the work spent on a single file, `None` means no limit
+#[derive(Debug, Clone, Default)]
+pub struct SplitLimits {
+    /// the maximum size of the file in bytes
+    pub max_file_bytes: Option<usize>,
+    /// the maximum length of a line in bytes, a longer line usually means a minified
+    /// or generated file
+    pub max_line_length: Option<usize>,
+    /// the maximum time spent parsing the file, it bounds every parse of the file,
+    /// the reparses recovering from syntax errors share a single timeout
+    pub parse_timeout: Option<Duration>,
+    /// the maximum depth of the syntax tree, measured with a cursor once the file is
+    /// parsed
+    pub max_tree_depth: Option<usize>,
+    /// what to do when a limit is exceeded
+    pub on_exceeded: LimitAction,
 }
 
 /// Configuration options for the devgen splitter.
@@ -100,9 +299,144 @@ pub struct Chunk {
 pub struct SplitOptions {
     /// the maximum number of lines for each chunk
     pub chunk_line_limit: usize,
+    /// the minimum number of lines for each chunk, a smaller chunk is merged with a
+    /// neighbour. `None` keeps the default heuristics of each splitter
+    pub min_chunk_lines: Option<usize>,
+    /// the overlap between consecutive chunks, `None` means the chunks don't overlap
+    pub overlap: Option<ChunkOverlap>,
+    /// the strategy used to choose the chunk boundaries
+    pub strategy: SplitStrategy,
+    /// recover the entities around the syntax errors of the code by masking the lines
+    /// that break the parser and parsing the code again. The masked lines still belong
+    /// to the chunks
+    pub tolerate_errors: bool,
+    /// the limits bounding the work spent on a file
+    pub limits: SplitLimits,
+    /// what to do with a minified or generated file
+    pub generated: GeneratedPolicy,
+    /// split the text outputs of the code cells of a Jupyter notebook too, they are
+    /// dropped by default
+    pub keep_notebook_outputs: bool,
+    /// find the SQL queries embedded in the string literals of Python, TypeScript and
+    /// JavaScript code, tagged with a `sql` template tag or a `language=sql` comment or
+    /// detected by their keywords, and add them as `EntityType::Query` entities of the
+    /// function enclosing them
+    pub embedded_sql: bool,
+    /// collect the functions, types and modules referenced by every chunk with the
+    /// reference query of the language, see `Chunk::references`
+    pub collect_references: bool,
+    /// add the imports as `EntityType::Import` entities and attach to every chunk the
+    /// imports it uses, see `Chunk::imports`
+    pub collect_imports: bool,
 }
 
+impl SplitOptions {
+    /// Checks that the options can be used to split code
+    ///
+    /// # Errors
+    ///
+    /// Returns `SplitError::InvalidOptions` if the chunk line limit is zero, if the
+    /// minimum chunk lines exceed the limit or if the overlap ratio is not a finite
+    /// positive number.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| {
+            Err(SplitError::InvalidOptions {
+                reason: reason.to_string(),
+            })
+        };
+        if self.chunk_line_limit == 0 {
+            return invalid("chunk_line_limit must be greater than 0");
+        }
+        if self
+            .min_chunk_lines
+            .is_some_and(|min_chunk_lines| min_chunk_lines > self.chunk_line_limit)
+        {
+            return invalid("min_chunk_lines must not exceed chunk_line_limit");
+        }
+        if let Some(ChunkOverlap::Ratio(ratio)) = self.overlap {
+            if !ratio.is_finite() || ratio < 0.0 {
+                return invalid("the overlap ratio must be a finite positive number");
+            }
+        }
+        Ok(())
+    }
+}
+
+mod diagnostics;
+mod error;
+mod generated;
 mod lang;
+mod notebook;
+mod rechunk;
 mod splitter;
-pub use lang::Lang;
-pub use splitter::split;
+mod validate;
+mod walk;
+pub use diagnostics::{
+    Diagnostics,
+    SplitResult,
+    SyntaxError,
+    SyntaxErrorKind,
+};
+pub use error::{
+    QueryErrorKind,
+    Result,
+    SplitError,
+};
+pub use generated::{
+    detect_generated,
+    GeneratedKind,
+};
+pub use lang::{
+    Lang,
+    LangConfig,
+};
+#[cfg(feature = "notebook")]
+pub use notebook::{
+    CellContent,
+    Notebook,
+};
+pub use notebook::{
+    CellType,
+    NotebookCell,
+};
+pub use rechunk::{
+    rechunk,
+    rechunk_with_diff,
+};
+pub use splitter::{
+    outline,
+    repo_map,
+    split,
+    split_with_diagnostics,
+    ApproximateTokenizer,
+    ChunkDiff,
+    ChunkHook,
+    IncrementalSplitter,
+    Outline,
+    OutlineItem,
+    RankedEntity,
+    RepoMap,
+    Splitter,
+    SplitterBuilder,
+    Tokenizer,
+};
+pub use tree_sitter::{
+    InputEdit,
+    Point,
+};
+pub use validate::{
+    validate_chunks,
+    ChunkIssue,
+};
+#[cfg(feature = "notebook")]
+pub use validate::{
+    validate_notebook_chunks,
+    CellIssue,
+};
+pub use walk::{
+    repo_map_dir,
+    split_dir,
+    RepoMapResult,
+    SplitDirIter,
+    WalkOptions,
+};
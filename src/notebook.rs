@@ -0,0 +1,500 @@
+//
+// notebook.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#[cfg(feature = "notebook")]
+use crate::{
+    diagnostics::{
+        Diagnostics,
+        SplitResult,
+    },
+    lang::{
+        Lang,
+        LangConfig,
+    },
+    splitter::{
+        self,
+        chunk_id,
+        ranges,
+    },
+    Chunk,
+    Limit,
+    Result,
+    SplitError,
+    SplitOptions,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+#[cfg(feature = "notebook")]
+use serde_json::{
+    value::RawValue,
+    Value,
+};
+#[cfg(feature = "notebook")]
+use std::collections::HashMap;
+use std::{
+    ops::Range,
+    path::Path,
+};
+
+/// the kernel language of a notebook without language metadata
+#[cfg(feature = "notebook")]
+const DEFAULT_KERNEL_LANGUAGE: &str = "python";
+
+/// The type of a notebook cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CellType {
+    /// a cell of code in the kernel language
+    Code,
+    /// a cell of Markdown text
+    Markdown,
+    /// a cell of raw text
+    Raw,
+}
+
+/// The notebook cell a chunk comes from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotebookCell {
+    /// the index of the cell in the notebook
+    pub index: usize,
+    /// the type of the cell
+    pub cell_type: CellType,
+    /// the chunk covers the text outputs of the cell instead of its source
+    pub output: bool,
+    /// the lines of the chunk in the source, or the outputs, of the cell
+    #[serde(default)]
+    pub line_range: Range<usize>,
+}
+
+/// The content of a Jupyter notebook cell
+#[cfg(feature = "notebook")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellContent {
+    /// the type of the cell
+    pub cell_type: CellType,
+    /// the source of the cell
+    pub source: String,
+    /// the text outputs of a code cell, the streams and the plain text results
+    pub outputs: String,
+}
+
+/// A Jupyter notebook, as stored in an `.ipynb` file
+#[cfg(feature = "notebook")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notebook {
+    /// the language of the kernel, e.g. `"python"`
+    pub language: Option<String>,
+    /// the cells of the notebook
+    pub cells: Vec<CellContent>,
+}
+
+#[cfg(feature = "notebook")]
+impl Notebook {
+    /// Parses the JSON of a notebook
+    ///
+    /// # Errors
+    ///
+    /// Returns `SplitError::InvalidNotebook` if the code is not the JSON of a notebook.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use devgen_splitter::{
+    ///     CellType,
+    ///     Notebook,
+    /// };
+    ///
+    /// let code = r#"{"cells": [{"cell_type": "code", "source": ["x = 1\n", "print(x)"]}]}"#;
+    /// let notebook = Notebook::parse(code).unwrap();
+    /// assert_eq!(notebook.cells[0].cell_type, CellType::Code);
+    /// assert_eq!(notebook.cells[0].source, "x = 1\nprint(x)");
+    /// ```
+    pub fn parse(code: &str) -> Result<Self> {
+        Ok(parse_notebook(code)?.0)
+    }
+
+    /// Returns the text a chunk of the notebook was split from, the source of its cell
+    /// or the text outputs of its cell
+    pub fn text(&self, cell: &NotebookCell) -> Option<&str> {
+        let notebook_cell = self.cells.get(cell.index)?;
+        Some(match cell.output {
+            true => &notebook_cell.outputs,
+            false => &notebook_cell.source,
+        })
+    }
+}
+
+/// The JSON of a notebook, the strings of the cells are kept raw to find their lines
+#[cfg(feature = "notebook")]
+#[derive(Deserialize)]
+struct RawNotebook<'a> {
+    #[serde(borrow)]
+    cells: Option<Vec<RawCell<'a>>>,
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[cfg(feature = "notebook")]
+#[derive(Deserialize)]
+struct RawCell<'a> {
+    cell_type: Option<String>,
+    #[serde(borrow)]
+    source: Option<&'a RawValue>,
+    #[serde(borrow)]
+    outputs: Option<&'a RawValue>,
+}
+
+#[cfg(feature = "notebook")]
+#[derive(Deserialize)]
+struct RawOutput<'a> {
+    output_type: Option<String>,
+    #[serde(borrow)]
+    text: Option<&'a RawValue>,
+    #[serde(borrow)]
+    data: Option<HashMap<String, &'a RawValue>>,
+}
+
+/// The line of the notebook file holding every line of the texts of a cell
+#[cfg(feature = "notebook")]
+struct CellLines {
+    source: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+/// A text joined from the strings of a notebook, with the line of the notebook file
+/// each string starts on
+#[cfg(feature = "notebook")]
+#[derive(Default)]
+struct JoinedText {
+    text: String,
+    /// the start of every non-empty string in the text and its line in the file
+    strings: Vec<(usize, usize)>,
+}
+
+#[cfg(feature = "notebook")]
+impl JoinedText {
+    /// Appends a multiline string of the notebook, stored as a string or a list of
+    /// lines, without its trailing newline
+    fn push(&mut self, file_line_starts: &[usize], code: &str, value: Option<&RawValue>) {
+        let start = self.text.len();
+        let strings = value.map_or(vec![], |value| {
+            serde_json::from_str::<Vec<&RawValue>>(value.get()).unwrap_or_else(|_| vec![value])
+        });
+        for string in strings {
+            let Ok(text) = serde_json::from_str::<String>(string.get()) else {
+                continue;
+            };
+            if !text.is_empty() {
+                // the raw value is borrowed from the code
+                let offset =
+                    (string.get().as_ptr() as usize).saturating_sub(code.as_ptr() as usize);
+                let line = file_line_starts.partition_point(|start| *start <= offset) - 1;
+                self.strings.push((self.text.len(), line));
+            }
+            self.text.push_str(&text);
+        }
+        if self.text.len() > start && self.text.ends_with('\n') {
+            self.text.pop();
+        }
+    }
+
+    /// Returns the line of the file holding every line of the text
+    fn file_lines(&self) -> Vec<usize> {
+        ranges::line_starts(&self.text)
+            .into_iter()
+            .take(self.text.lines().count())
+            .map(|start| {
+                let index = self.strings.partition_point(|(string, _)| *string <= start);
+                self.strings[index.saturating_sub(1)].1
+            })
+            .collect()
+    }
+}
+
+/// Parses the JSON of a notebook and finds the lines of the file holding its cells
+#[cfg(feature = "notebook")]
+fn parse_notebook(code: &str) -> Result<(Notebook, Vec<CellLines>)> {
+    let notebook: RawNotebook = serde_json::from_str(code).map_err(|e| invalid_notebook(&e))?;
+    let metadata = &notebook.metadata;
+    let language = metadata["kernelspec"]["language"]
+        .as_str()
+        .or_else(|| metadata["language_info"]["name"].as_str())
+        .map(|language| language.to_string());
+    let file_line_starts = ranges::line_starts(code);
+    let mut cells = vec![];
+    let mut cell_lines = vec![];
+    for cell in notebook
+        .cells
+        .ok_or_else(|| invalid_notebook(&"the notebook has no cells"))?
+    {
+        let cell_type = match cell.cell_type.as_deref() {
+            Some("code") => CellType::Code,
+            Some("markdown") => CellType::Markdown,
+            Some("raw") => CellType::Raw,
+            cell_type => {
+                return Err(invalid_notebook(&format!(
+                    "unknown cell type {:?}",
+                    cell_type
+                )))
+            }
+        };
+        let mut source = JoinedText::default();
+        source.push(&file_line_starts, code, cell.source);
+        let mut outputs = JoinedText::default();
+        let raw_outputs = cell.outputs.map_or(vec![], |raw_outputs| {
+            serde_json::from_str::<Vec<&RawValue>>(raw_outputs.get()).unwrap_or_default()
+        });
+        for (i, output) in raw_outputs
+            .into_iter()
+            .filter_map(|output| serde_json::from_str::<RawOutput>(output.get()).ok())
+            .filter_map(|output| match output.output_type.as_deref() {
+                Some("stream") => Some(output.text),
+                Some("execute_result") | Some("display_data") => output
+                    .data
+                    .and_then(|data| data.get("text/plain").copied())
+                    .map(Some),
+                _ => None,
+            })
+            .enumerate()
+        {
+            if i > 0 {
+                outputs.text.push('\n');
+            }
+            outputs.push(&file_line_starts, code, output);
+        }
+        cell_lines.push(CellLines {
+            source: source.file_lines(),
+            outputs: outputs.file_lines(),
+        });
+        cells.push(CellContent {
+            cell_type,
+            source: source.text,
+            outputs: outputs.text,
+        });
+    }
+    Ok((Notebook { language, cells }, cell_lines))
+}
+
+/// Returns true if the file is a Jupyter notebook
+pub(crate) fn is_notebook(filename: &str) -> bool {
+    Path::new(filename)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+}
+
+/// Splits every cell of a notebook, the code cells with the kernel language and the
+/// Markdown cells with the Markdown splitter
+///
+/// The line ranges of the chunks are the lines of the notebook file holding them, the
+/// lines of a chunk in its cell are kept in `NotebookCell::line_range`. A cell without
+/// a supported language is split by lines only.
+#[cfg(feature = "notebook")]
+pub(crate) fn split_notebook(
+    filename: &str,
+    code: &str,
+    options: &SplitOptions,
+    mut split_cell: impl FnMut(&'static LangConfig, &str) -> Result<SplitResult>,
+) -> Result<SplitResult> {
+    options.validate()?;
+    // the line length isn't checked, the outputs of a cell are often a single line
+    if options
+        .limits
+        .max_file_bytes
+        .is_some_and(|max_file_bytes| code.len() > max_file_bytes)
+    {
+        return splitter::split_limited(filename, code, options, Limit::FileBytes);
+    }
+    let (notebook, cell_lines) = parse_notebook(code)?;
+    let kernel = Lang::from_name(
+        notebook
+            .language
+            .as_deref()
+            .unwrap_or(DEFAULT_KERNEL_LANGUAGE),
+    );
+    let mut result = SplitResult {
+        chunks: vec![],
+        diagnostics: Diagnostics::default(),
+    };
+    for (index, (cell, lines)) in notebook.cells.iter().zip(cell_lines).enumerate() {
+        let lang_config = match cell.cell_type {
+            CellType::Code => kernel,
+            CellType::Markdown => Lang::from_name("Markdown"),
+            CellType::Raw => None,
+        };
+        let cell_result = match lang_config {
+            Some(lang_config) => split_cell(lang_config, &cell.source)?,
+            None => split_text(filename, &cell.source, options),
+        };
+        let mut chunks = vec![];
+        for mut chunk in cell_result.chunks {
+            if chunk.lang.is_none() {
+                chunk.lang = lang_config.map(|lang_config| lang_config.name().to_string());
+            }
+            chunks.push(file_chunk(
+                chunk,
+                index,
+                cell.cell_type,
+                false,
+                &lines.source,
+            ));
+        }
+        if options.keep_notebook_outputs && !cell.outputs.is_empty() {
+            let outputs = split_text(filename, &cell.outputs, options);
+            chunks.extend(
+                outputs
+                    .chunks
+                    .into_iter()
+                    .map(|chunk| file_chunk(chunk, index, cell.cell_type, true, &lines.outputs)),
+            );
+        }
+        result.chunks.extend(chunks);
+        let diagnostics = cell_result.diagnostics;
+        result.diagnostics.failed_entities += diagnostics.failed_entities;
+        result
+            .diagnostics
+            .syntax_errors
+            .extend(diagnostics.syntax_errors.into_iter().map(|mut error| {
+                error.line_range = file_range(&error.line_range, &lines.source);
+                error.cell = Some(index);
+                error
+            }));
+        result.diagnostics.masked_line_ranges.extend(
+            diagnostics
+                .masked_line_ranges
+                .iter()
+                .map(|line_range| file_range(line_range, &lines.source)),
+        );
+        result.diagnostics.exceeded_limit = result
+            .diagnostics
+            .exceeded_limit
+            .or(diagnostics.exceeded_limit);
+    }
+    chunk_id::assign_ids(filename, &mut result.chunks);
+    result.diagnostics.line_fallback = result.chunks.iter().all(|chunk| chunk.entities.is_empty());
+    Ok(result)
+}
+
+/// Sets the cell of a chunk split from a text of the cell and moves its lines to the
+/// lines of the notebook file holding them
+#[cfg(feature = "notebook")]
+fn file_chunk(
+    mut chunk: Chunk,
+    index: usize,
+    cell_type: CellType,
+    output: bool,
+    file_lines: &[usize],
+) -> Chunk {
+    let line = |line: usize| file_line(line, file_lines);
+    // the end line of the ranges of the entities and the imports is included
+    let lines = |range: &Range<usize>| line(range.start)..line(range.end);
+    chunk.cell = Some(NotebookCell {
+        index,
+        cell_type,
+        output,
+        line_range: chunk.line_range.clone(),
+    });
+    chunk.line_range = file_range(&chunk.line_range, file_lines);
+    for entity in chunk.entities.iter_mut() {
+        entity.completed_line_range = lines(&entity.completed_line_range);
+        entity.chunk_line_range = lines(&entity.chunk_line_range);
+        entity.parent_line_range = entity.parent_line_range.as_ref().map(lines);
+    }
+    for reference in chunk.references.iter_mut() {
+        reference.line = line(reference.line);
+    }
+    for import in chunk.imports.iter_mut() {
+        import.line_range = lines(&import.line_range);
+    }
+    chunk
+}
+
+/// Returns the line of the notebook file holding a line of a text of a cell, the line
+/// after the text for a line past its end
+#[cfg(feature = "notebook")]
+fn file_line(line: usize, file_lines: &[usize]) -> usize {
+    match file_lines.get(line) {
+        Some(file_line) => *file_line,
+        None => file_lines.last().map_or(0, |last| last + 1),
+    }
+}
+
+/// Returns the lines of the notebook file holding a range of lines of a text of a cell,
+/// the end of the range is excluded
+#[cfg(feature = "notebook")]
+fn file_range(range: &Range<usize>, file_lines: &[usize]) -> Range<usize> {
+    match range.is_empty() {
+        true => file_line(range.start, file_lines)..file_line(range.start, file_lines),
+        false => file_line(range.start, file_lines)..file_line(range.end - 1, file_lines) + 1,
+    }
+}
+
+/// Splits a text without a language by lines only
+#[cfg(feature = "notebook")]
+fn split_text(filename: &str, text: &str, options: &SplitOptions) -> SplitResult {
+    SplitResult {
+        chunks: splitter::split_lines(filename, text, options.chunk_line_limit),
+        diagnostics: Diagnostics {
+            line_fallback: true,
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(feature = "notebook")]
+fn invalid_notebook(reason: &dyn std::fmt::Display) -> SplitError {
+    SplitError::InvalidNotebook {
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(all(test, feature = "notebook"))]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use serde_json::json;
+
+    #[rstest]
+    #[case("\"a\\nb\\n\"", "a\nb", vec![0, 0])]
+    #[case("[\n \"a\\n\",\n \"b\"\n]", "a\nb", vec![1, 2])]
+    #[case("[\n \"\",\n \"a\\n\",\n 1,\n \"b\"\n]", "a\nb", vec![2, 4])]
+    #[case("null", "", vec![])]
+    fn test_joined_text(#[case] code: &str, #[case] text: &str, #[case] file_lines: Vec<usize>) {
+        let value = serde_json::from_str::<&RawValue>(code).unwrap();
+        let mut joined = JoinedText::default();
+        joined.push(&ranges::line_starts(code), code, Some(value));
+        assert_eq!(joined.text, text);
+        assert_eq!(joined.file_lines(), file_lines);
+    }
+
+    #[test]
+    fn test_parse_notebook() {
+        let code = json!({
+            "metadata": {"kernelspec": {"language": "R", "name": "ir"}},
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title\n", "text"]},
+                {
+                    "cell_type": "code",
+                    "source": "print(1)",
+                    "outputs": [
+                        {"output_type": "stream", "name": "stdout", "text": ["1\n"]},
+                        {"output_type": "display_data", "data": {"image/png": "..."}},
+                        {"output_type": "execute_result", "data": {"text/plain": ["[1] 1"]}}
+                    ]
+                }
+            ]
+        })
+        .to_string();
+        let notebook = Notebook::parse(&code).unwrap();
+        assert_eq!(notebook.language.as_deref(), Some("R"));
+        assert_eq!(notebook.cells[0].cell_type, CellType::Markdown);
+        assert_eq!(notebook.cells[0].source, "# Title\ntext");
+        assert_eq!(notebook.cells[1].outputs, "1\n[1] 1");
+        assert!(Notebook::parse("{}").is_err());
+        assert!(Notebook::parse("not json").is_err());
+    }
+}
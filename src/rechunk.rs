@@ -0,0 +1,236 @@
+//
+// rechunk.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+pub(crate) mod hunk;
+
+use crate::{
+    split,
+    splitter::ranges::intersects,
+    Chunk,
+    ChunkDiff,
+    Result,
+    SplitOptions,
+};
+use hunk::Hunk;
+use std::ops::Range;
+
+/// Re-chunks a file from its old and new contents and the chunks of the old contents
+///
+/// The changed lines are found with a line diff, then the new contents are split and
+/// compared with the previous chunks.
+///
+/// # Arguments
+///
+/// * `filename` - The name of the file, used to detect the language.
+/// * `old_code` - The contents the previous chunks were split from.
+/// * `new_code` - The new contents of the file.
+/// * `previous` - The chunks of the old contents.
+/// * `options` - The options used to split the old contents.
+///
+/// # Returns
+///
+/// A `ChunkDiff` where `added` holds the new chunks touching a changed line, `removed`
+/// the previous chunks touching a changed line and `unchanged` the other previous
+/// chunks, moved to their line ranges in the new contents.
+///
+/// # Example
+///
+/// ```
+/// use devgen_splitter::{
+///     rechunk,
+///     split,
+///     SplitOptions,
+/// };
+///
+/// let options = SplitOptions {
+///     chunk_line_limit: 3,
+///     ..Default::default()
+/// };
+/// let old_code = "fn a() {\n    run();\n}\n\nfn b() {\n    run();\n}\n";
+/// let new_code = "fn a() {\n    run();\n}\n\nfn b() {\n    stop();\n}\n";
+/// let previous = split("example.rs", old_code, &options).unwrap();
+/// let diff = rechunk("example.rs", old_code, new_code, &previous, &options).unwrap();
+/// assert_eq!(diff.unchanged.len(), 1);
+/// ```
+pub fn rechunk(
+    filename: &str,
+    old_code: &str,
+    new_code: &str,
+    previous: &[Chunk],
+    options: &SplitOptions,
+) -> Result<ChunkDiff> {
+    let old_lines = old_code.lines().collect::<Vec<&str>>();
+    let new_lines = new_code.lines().collect::<Vec<&str>>();
+    let hunks = hunk::diff_lines(&old_lines, &new_lines);
+    rechunk_hunks(filename, new_code, &hunks, previous, options)
+}
+
+/// Re-chunks a file from its new contents, a unified diff of the file and the chunks
+/// of the old contents
+///
+/// The diff is a plain text unified diff of a single file, as printed by `git diff` or
+/// `diff -u`, the context lines around the changes are ignored. See `rechunk` for the
+/// returned chunks.
+///
+/// # Errors
+///
+/// Returns an error if a hunk header of the diff is invalid or if the new contents
+/// can't be split.
+pub fn rechunk_with_diff(
+    filename: &str,
+    new_code: &str,
+    diff: &str,
+    previous: &[Chunk],
+    options: &SplitOptions,
+) -> Result<ChunkDiff> {
+    let hunks = hunk::parse_unified_diff(diff)?;
+    rechunk_hunks(filename, new_code, &hunks, previous, options)
+}
+
+fn rechunk_hunks(
+    filename: &str,
+    new_code: &str,
+    hunks: &[Hunk],
+    previous: &[Chunk],
+    options: &SplitOptions,
+) -> Result<ChunkDiff> {
+    let chunks = split(filename, new_code, options)?;
+    Ok(diff_chunks(chunks, hunks, previous))
+}
+
+/// Compares the chunks of the new code with the previous chunks, a previous chunk is
+/// unchanged if no hunk touches it and it is found among the new chunks once moved to
+/// the new lines
+pub(crate) fn diff_chunks(chunks: Vec<Chunk>, hunks: &[Hunk], previous: &[Chunk]) -> ChunkDiff {
+    let mut diff = ChunkDiff::default();
+    let mut kept = vec![];
+    for chunk in previous {
+        if touches_hunks(chunk, hunks) {
+            diff.removed.push(chunk.clone());
+            continue;
+        }
+        let moved = map_chunk(chunk, hunks);
+        if chunks.contains(&moved) {
+            kept.push(moved);
+        } else {
+            // the chunk boundaries moved although the lines didn't change
+            diff.removed.push(chunk.clone());
+        }
+    }
+    diff.added = chunks
+        .into_iter()
+        .filter(|chunk| !kept.contains(chunk))
+        .collect();
+    diff.unchanged = kept;
+    diff
+}
+
+/// Returns true if a hunk changes a line of the chunk or of one of its entities
+fn touches_hunks(chunk: &Chunk, hunks: &[Hunk]) -> bool {
+    hunks.iter().any(|hunk| {
+        touches(&chunk.line_range, hunk)
+            || chunk
+                .entities
+                .iter()
+                .any(|entity| touches(&entity.completed_line_range, hunk))
+    })
+}
+
+fn touches(line_range: &Range<usize>, hunk: &Hunk) -> bool {
+    let old = &hunk.old_line_range;
+    if old.is_empty() {
+        // the lines are inserted before `old.start`
+        line_range.start < old.start && old.start < line_range.end
+    } else {
+        intersects(line_range, old)
+    }
+}
+
+/// Maps the first line of a range in the old code to the new code
+fn map_start(line: usize, hunks: &[Hunk]) -> usize {
+    let mut mapped = line;
+    for hunk in hunks {
+        if line < hunk.old_line_range.start {
+            break;
+        }
+        if line >= hunk.old_line_range.end {
+            mapped = line + hunk.new_line_range.end - hunk.old_line_range.end;
+        } else {
+            return hunk.new_line_range.start;
+        }
+    }
+    mapped
+}
+
+/// Maps the exclusive end of a range in the old code to the new code
+fn map_end(line: usize, hunks: &[Hunk]) -> usize {
+    let mut mapped = line;
+    for hunk in hunks {
+        if line <= hunk.old_line_range.start {
+            break;
+        }
+        if line >= hunk.old_line_range.end {
+            mapped = line + hunk.new_line_range.end - hunk.old_line_range.end;
+        } else {
+            return hunk.new_line_range.end;
+        }
+    }
+    mapped
+}
+
+fn map_range(line_range: &Range<usize>, hunks: &[Hunk]) -> Range<usize> {
+    map_start(line_range.start, hunks)..map_end(line_range.end, hunks)
+}
+
+/// Moves the chunk, its entities, its references and its imports to their lines in
+/// the new code
+fn map_chunk(chunk: &Chunk, hunks: &[Hunk]) -> Chunk {
+    let mut chunk = chunk.clone();
+    chunk.line_range = map_range(&chunk.line_range, hunks);
+    for entity in chunk.entities.iter_mut() {
+        entity.completed_line_range = map_range(&entity.completed_line_range, hunks);
+        entity.chunk_line_range = map_range(&entity.chunk_line_range, hunks);
+        entity.parent_line_range = entity
+            .parent_line_range
+            .as_ref()
+            .map(|range| map_range(range, hunks));
+    }
+    for reference in chunk.references.iter_mut() {
+        reference.line = map_start(reference.line, hunks);
+    }
+    for import in chunk.imports.iter_mut() {
+        // the end line of an import is included
+        import.line_range =
+            map_start(import.line_range.start, hunks)..map_start(import.line_range.end, hunks);
+    }
+    chunk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0..3, 0..3)]
+    #[case(3..6, 5..8)]
+    #[case(6..9, 8..11)]
+    #[case(2..7, 2..9)]
+    fn test_map_range(#[case] line_range: Range<usize>, #[case] expected: Range<usize>) {
+        // two lines inserted before line 3, line 4 replaced by one line
+        let hunks = vec![
+            Hunk {
+                old_line_range: 3..3,
+                new_line_range: 3..5,
+            },
+            Hunk {
+                old_line_range: 4..5,
+                new_line_range: 6..7,
+            },
+        ];
+        assert_eq!(map_range(&line_range, &hunks), expected);
+    }
+}
@@ -0,0 +1,277 @@
+//
+// hunk.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use crate::{
+    Result,
+    SplitError,
+};
+use std::ops::Range;
+
+/// the largest edit distance searched by the Myers algorithm, a larger change is
+/// reported as a single hunk so the search stays bounded in time and memory
+const MAX_EDIT_DISTANCE: usize = 1024;
+
+/// A run of changed lines, the line ranges are 0-based and end-exclusive
+///
+/// An empty old line range is a pure insertion before that line, an empty new line
+/// range is a pure deletion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Hunk {
+    /// the changed lines in the old code
+    pub old_line_range: Range<usize>,
+    /// the changed lines in the new code
+    pub new_line_range: Range<usize>,
+}
+
+/// Computes the hunks turning the old lines into the new lines with the Myers
+/// algorithm
+///
+/// The lines between the common prefix and suffix become a single hunk when more than
+/// `MAX_EDIT_DISTANCE` lines are removed and added.
+pub(crate) fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Hunk> {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+    let mut hunks = vec![];
+    let mut old_line = 0;
+    let mut new_line = 0;
+    let matches = myers_matches(old_middle, new_middle)
+        .unwrap_or_default()
+        .into_iter()
+        .chain(std::iter::once((old_middle.len(), new_middle.len())));
+    for (old_match, new_match) in matches {
+        if old_match > old_line || new_match > new_line {
+            hunks.push(Hunk {
+                old_line_range: prefix + old_line..prefix + old_match,
+                new_line_range: prefix + new_line..prefix + new_match,
+            });
+        }
+        old_line = old_match + 1;
+        new_line = new_match + 1;
+    }
+    hunks
+}
+
+/// Returns the pairs of matching lines of the shortest edit script, in order, or
+/// `None` if the edit distance exceeds `MAX_EDIT_DISTANCE`
+fn myers_matches(old: &[&str], new: &[&str]) -> Option<Vec<(usize, usize)>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    // the diagonals -d - 1..=d + 1 of `v` before every round d, the only ones read
+    // back when walking the path backwards
+    let mut trace = vec![];
+    'search: for d in 0..=max as isize {
+        if d as usize > MAX_EDIT_DISTANCE {
+            return None;
+        }
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let index = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+    let mut matches = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let index = (k + d + 1) as usize;
+        let prev_k = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + d + 1) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+        if d > 0 {
+            x = prev_x;
+            y = prev_y;
+        }
+    }
+    matches.reverse();
+    Some(matches)
+}
+
+/// Parses the hunks of a unified diff of a single file
+///
+/// The context lines around the changes are not part of the hunks, so every hunk
+/// covers only the removed and added lines.
+pub(crate) fn parse_unified_diff(diff: &str) -> Result<Vec<Hunk>> {
+    let mut hunks = vec![];
+    // the next old and new line, `None` outside of a hunk
+    let mut position: Option<(usize, usize)> = None;
+    let mut current: Option<Hunk> = None;
+    for line in diff.lines() {
+        if line.starts_with("@@") {
+            hunks.extend(current.take());
+            position = Some(parse_hunk_header(line)?);
+            continue;
+        }
+        let Some((old_line, new_line)) = position.as_mut() else {
+            continue;
+        };
+        match line.chars().next() {
+            Some('-') | Some('+') => {
+                let hunk = current.get_or_insert(Hunk {
+                    old_line_range: *old_line..*old_line,
+                    new_line_range: *new_line..*new_line,
+                });
+                if line.starts_with('-') {
+                    *old_line += 1;
+                    hunk.old_line_range.end = *old_line;
+                } else {
+                    *new_line += 1;
+                    hunk.new_line_range.end = *new_line;
+                }
+            }
+            // "\ No newline at end of file"
+            Some('\\') => {}
+            Some(' ') | None => {
+                hunks.extend(current.take());
+                *old_line += 1;
+                *new_line += 1;
+            }
+            _ => {
+                // the header of the next file
+                hunks.extend(current.take());
+                position = None;
+            }
+        }
+    }
+    hunks.extend(current);
+    Ok(hunks)
+}
+
+/// Parses a hunk header such as `@@ -10,7 +10,8 @@`, returning the 0-based first old
+/// and new lines
+fn parse_hunk_header(line: &str) -> Result<(usize, usize)> {
+    let mut parts = line.split_whitespace().skip(1);
+    let (Some(old), Some(new)) = (parts.next(), parts.next()) else {
+        return Err(invalid_diff(line));
+    };
+    let parse = |range: &str, sign: char| -> Result<usize> {
+        let range = range.strip_prefix(sign).ok_or_else(|| invalid_diff(line))?;
+        let number = |number: &str| number.parse::<usize>().map_err(|_| invalid_diff(line));
+        let (start, count) = match range.split_once(',') {
+            Some((start, count)) => (number(start)?, number(count)?),
+            None => (number(range)?, 1),
+        };
+        // an empty range starts after the given line
+        Ok(if count == 0 {
+            start
+        } else {
+            start.saturating_sub(1)
+        })
+    };
+    Ok((parse(old, '-')?, parse(new, '+')?))
+}
+
+fn invalid_diff(line: &str) -> SplitError {
+    SplitError::InvalidDiff {
+        line: line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn hunk(old_line_range: Range<usize>, new_line_range: Range<usize>) -> Hunk {
+        Hunk {
+            old_line_range,
+            new_line_range,
+        }
+    }
+
+    #[rstest]
+    #[case("a b c d", "a b c d", vec![])]
+    #[case("a b c d", "a x c d", vec![hunk(1..2, 1..2)])]
+    #[case("a b c d", "a b x y c d", vec![hunk(2..2, 2..4)])]
+    #[case("a b c d", "b c", vec![hunk(0..1, 0..0), hunk(3..4, 2..2)])]
+    #[case("a b c d e f", "a c d x f", vec![hunk(1..2, 1..1), hunk(4..5, 3..4)])]
+    #[case("", "a b", vec![hunk(0..0, 0..2)])]
+    fn test_diff_lines(#[case] old: &str, #[case] new: &str, #[case] expected: Vec<Hunk>) {
+        let old = old.split_whitespace().collect::<Vec<&str>>();
+        let new = new.split_whitespace().collect::<Vec<&str>>();
+        assert_eq!(diff_lines(&old, &new), expected);
+    }
+
+    #[rstest]
+    #[case(MAX_EDIT_DISTANCE / 8, vec![hunk(0..128, 0..128), hunk(129..257, 129..257)])]
+    // the matching line in the middle is beyond the edit distance
+    #[case(MAX_EDIT_DISTANCE / 2, vec![hunk(0..1025, 0..1025)])]
+    fn test_diff_lines_edit_distance(#[case] count: usize, #[case] expected: Vec<Hunk>) {
+        let lines = |prefix: &str| {
+            let mut lines = (0..2 * count)
+                .map(|i| format!("{} {}", prefix, i))
+                .collect::<Vec<String>>();
+            lines.insert(count, "same".to_string());
+            lines
+        };
+        let (old, new) = (lines("old"), lines("new"));
+        let old = old.iter().map(String::as_str).collect::<Vec<&str>>();
+        let new = new.iter().map(String::as_str).collect::<Vec<&str>>();
+        assert_eq!(diff_lines(&old, &new), expected);
+    }
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let diff = "\
+--- a/lib.rs
++++ b/lib.rs
+@@ -1,4 +1,4 @@
+ a
+-b
++x
+ c
+ d
+@@ -10,3 +10,5 @@ fn main() {
+ j
+ k
++y
++z
+ l
+@@ -20 +22,0 @@
+-t
+";
+        assert_eq!(
+            parse_unified_diff(diff).unwrap(),
+            vec![hunk(1..2, 1..2), hunk(11..11, 11..13), hunk(19..20, 22..22)]
+        );
+        assert!(parse_unified_diff("@@ -a +b @@").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//
+// balanced_splitter.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    CodeChunk,
+    CodeEntity,
+};
+use std::ops::Range;
+use tree_sitter::{
+    Node,
+    Tree,
+};
+
+/// the cost of cutting through one level of syntax nodes
+const DEPTH_COST: f64 = 4.0;
+/// the cost of a cut that is not next to a blank line
+const NON_BLANK_COST: f64 = 2.0;
+/// the cost of a cut that is not at the start or the end of an entity
+const NON_ENTITY_COST: f64 = 3.0;
+/// the cost of a chunk whose size is far away from the balanced chunk size
+const SIZE_COST: f64 = 10.0;
+
+/// Splits the code into chunks by choosing the cut points with the lowest total cost
+///
+/// Every line boundary is a candidate cut point. Cutting through deeply nested
+/// syntax is expensive, while cutting at a blank line or at the edge of an entity
+/// is cheap. The cut points are chosen by dynamic programming, so that every chunk
+/// stays under the chunk line limit and the chunk sizes stay close to each other.
+pub(crate) fn split_balanced(
+    tree: &Tree,
+    lines: &[&str],
+    entities: &[(CodeEntity, Vec<Node>)],
+    chunk_line_limit: usize,
+) -> Vec<CodeChunk> {
+    let line_count = lines.len();
+    if line_count == 0 {
+        return vec![];
+    }
+    let limit = chunk_line_limit.max(1);
+    let cut_costs = cut_costs(tree, lines, entities);
+    let chunk_count = line_count.div_ceil(limit);
+    let balanced_size = line_count.div_ceil(chunk_count) as f64;
+    // best[j] is the lowest cost to split the first j lines, cut[j] is the start of
+    // the last chunk in that split
+    let mut best = vec![f64::INFINITY; line_count + 1];
+    let mut cut = vec![0; line_count + 1];
+    best[0] = 0.0;
+    for end in 1..=line_count {
+        let cut_cost = if end < line_count {
+            cut_costs[end]
+        } else {
+            0.0
+        };
+        for start in end.saturating_sub(limit)..end {
+            let deviation = ((end - start) as f64 - balanced_size) / balanced_size;
+            let cost = best[start] + cut_cost + SIZE_COST * deviation * deviation;
+            if cost < best[end] {
+                best[end] = cost;
+                cut[end] = start;
+            }
+        }
+    }
+    let mut ranges = vec![];
+    let mut end = line_count;
+    while end > 0 {
+        ranges.push(cut[end]..end);
+        end = cut[end];
+    }
+    ranges.reverse();
+    ranges
+        .into_iter()
+        .map(|line_range| CodeChunk {
+            entities: entities
+                .iter()
+                .filter(|(entity, _)| intersects(entity, &line_range))
+                .map(|(entity, _)| entity.clone())
+                .collect(),
+            line_range,
+        })
+        .collect()
+}
+
+/// Computes the cost of a cut before each line
+fn cut_costs(tree: &Tree, lines: &[&str], entities: &[(CodeEntity, Vec<Node>)]) -> Vec<f64> {
+    let line_count = lines.len();
+    // the number of named nodes crossing the boundary before each line, counted by a
+    // difference array
+    let mut depth_delta = vec![0i64; line_count + 2];
+    let mut cursor = tree.walk();
+    'walk: loop {
+        let node = cursor.node();
+        let (start_row, end_row) = (node.start_position().row, node.end_position().row);
+        if node.is_named() && start_row < end_row {
+            depth_delta[(start_row + 1).min(line_count + 1)] += 1;
+            depth_delta[(end_row + 1).min(line_count + 1)] -= 1;
+        }
+        if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'walk;
+            }
+        }
+    }
+    let mut entity_edges = vec![false; line_count + 1];
+    for (entity, _) in entities {
+        let (start, end) = (entity.start_line(), entity.body_line_range.end);
+        if start <= line_count {
+            entity_edges[start] = true;
+        }
+        if end < line_count {
+            entity_edges[end + 1] = true;
+        }
+    }
+    let mut costs = vec![0.0; line_count + 1];
+    let mut depth = 0;
+    for line in 0..=line_count {
+        depth += depth_delta[line];
+        if line == 0 || line == line_count {
+            continue;
+        }
+        let blank = lines[line - 1].trim().is_empty() || lines[line].trim().is_empty();
+        costs[line] = DEPTH_COST * depth.max(0) as f64
+            + if blank { 0.0 } else { NON_BLANK_COST }
+            + if entity_edges[line] {
+                0.0
+            } else {
+                NON_ENTITY_COST
+            };
+    }
+    costs
+}
+
+fn intersects(entity: &CodeEntity, line_range: &Range<usize>) -> bool {
+    entity.start_line() < line_range.end && entity.body_line_range.end >= line_range.start
+}
@@ -0,0 +1,398 @@
+//
+// builder.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    cache::QueryCache,
+    chunk_id,
+    split_code,
+    token_splitter::{
+        self,
+        ApproximateTokenizer,
+        Tokenizer,
+    },
+};
+use crate::{
+    diagnostics::SplitResult,
+    lang::{
+        Lang,
+        LangConfig,
+    },
+    Chunk,
+    Result,
+    SplitError,
+    SplitOptions,
+};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+use tree_sitter::{
+    Parser,
+    Query,
+};
+
+/// A hook called on every chunk before it is returned, with the filename and the
+/// text of the chunk
+pub type ChunkHook = dyn Fn(&str, &str, &mut Chunk) + Send + Sync;
+
+/// A reusable splitter that owns its parsers and its compiled queries
+///
+/// A `Splitter` is built once with `Splitter::builder()` and can be shared across
+/// threads, every thread takes a parser from the pool of the splitter.
+///
+/// # Example
+///
+/// ```
+/// use devgen_splitter::{
+///     SplitOptions,
+///     Splitter,
+/// };
+///
+/// let splitter = Splitter::builder()
+///     .options(SplitOptions {
+///         chunk_line_limit: 5,
+///         ..Default::default()
+///     })
+///     .build();
+/// let code = "fn main() { println!(\"Hello, world!\"); }";
+/// let chunks = splitter.split("example.rs", code).unwrap();
+/// ```
+pub struct Splitter {
+    options: SplitOptions,
+    /// the language configs consulted before the bundled ones, keyed by file extension
+    lang_overrides: HashMap<String, &'static LangConfig>,
+    /// the queries used instead of the bundled ones, keyed by language name
+    queries: HashMap<String, String>,
+    tokenizer: Arc<dyn Tokenizer>,
+    max_tokens: Option<usize>,
+    hooks: Vec<Arc<ChunkHook>>,
+    query_cache: QueryCache,
+    parsers: Mutex<Vec<Parser>>,
+}
+
+/// The builder of a `Splitter`
+pub struct SplitterBuilder {
+    options: SplitOptions,
+    lang_overrides: HashMap<String, &'static LangConfig>,
+    queries: HashMap<String, String>,
+    tokenizer: Arc<dyn Tokenizer>,
+    max_tokens: Option<usize>,
+    hooks: Vec<Arc<ChunkHook>>,
+}
+
+impl Default for SplitterBuilder {
+    fn default() -> Self {
+        Self {
+            options: SplitOptions::default(),
+            lang_overrides: HashMap::new(),
+            queries: HashMap::new(),
+            tokenizer: Arc::new(ApproximateTokenizer),
+            max_tokens: None,
+            hooks: vec![],
+        }
+    }
+}
+
+impl SplitterBuilder {
+    /// Sets the options used by every split
+    pub fn options(mut self, options: SplitOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Uses the language config for the files with the given extension, e.g. `"h"`
+    pub fn language(mut self, file_extension: &str, lang_config: &'static LangConfig) -> Self {
+        self.lang_overrides
+            .insert(file_extension.to_string(), lang_config);
+        self
+    }
+
+    /// Uses the query instead of the bundled one for the language, e.g. `"Rust"`
+    ///
+    /// The query must follow the capture names of the bundled queries, such as
+    /// `@function.definition` and `@function.name`.
+    pub fn query(mut self, lang: &str, query: &str) -> Self {
+        self.queries.insert(lang.to_string(), query.to_string());
+        self
+    }
+
+    /// Sets the tokenizer used to count the tokens of a chunk, an approximation of
+    /// four characters per token is used by default
+    pub fn tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Splits every chunk with more tokens than `max_tokens` into smaller chunks
+    pub fn max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Adds a hook called on every chunk before it is returned
+    pub fn hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str, &str, &mut Chunk) + Send + Sync + 'static,
+    {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Builds the splitter
+    pub fn build(self) -> Splitter {
+        Splitter {
+            options: self.options,
+            lang_overrides: self.lang_overrides,
+            queries: self.queries,
+            tokenizer: self.tokenizer,
+            max_tokens: self.max_tokens,
+            hooks: self.hooks,
+            query_cache: QueryCache::default(),
+            parsers: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl Splitter {
+    /// Creates a builder of the splitter
+    pub fn builder() -> SplitterBuilder {
+        SplitterBuilder::default()
+    }
+
+    /// Returns the options used by every split
+    pub fn options(&self) -> &SplitOptions {
+        &self.options
+    }
+
+    /// Splits the code into chunks, the language is detected from the filename
+    ///
+    /// See `devgen_splitter::split` for the details of the returned chunks.
+    pub fn split(&self, filename: &str, code: &str) -> Result<Vec<Chunk>> {
+        Ok(self.split_with_diagnostics(filename, code)?.chunks)
+    }
+
+    /// Splits the code into chunks and reports the diagnostics of the split
+    ///
+    /// See `devgen_splitter::split_with_diagnostics` for the details of the diagnostics.
+    pub fn split_with_diagnostics(&self, filename: &str, code: &str) -> Result<SplitResult> {
+        #[cfg(feature = "notebook")]
+        if crate::notebook::is_notebook(filename) {
+            // the chunks of a cell are finished against the source of the cell
+            return crate::notebook::split_notebook(
+                filename,
+                code,
+                &self.options,
+                |lang_config, source| {
+                    let result = self.with_parser(|parser| {
+                        split_code(
+                            filename,
+                            parser,
+                            lang_config,
+                            &|lang_config| self.query(lang_config),
+                            source,
+                            &self.options,
+                        )
+                    })?;
+                    Ok(SplitResult {
+                        chunks: self.finish(filename, source, result.chunks),
+                        diagnostics: result.diagnostics,
+                    })
+                },
+            );
+        }
+        let lang_config =
+            self.lang_config(filename, code)
+                .ok_or_else(|| SplitError::UnsupportedLanguage {
+                    filename: filename.to_string(),
+                })?;
+        let result = self.with_parser(|parser| {
+            split_code(
+                filename,
+                parser,
+                lang_config,
+                &|lang_config| self.query(lang_config),
+                code,
+                &self.options,
+            )
+        })?;
+        Ok(SplitResult {
+            chunks: self.finish(filename, code, result.chunks),
+            diagnostics: result.diagnostics,
+        })
+    }
+
+    /// Reads the file and splits it into chunks
+    pub fn split_path<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Chunk>> {
+        let path = path.as_ref();
+        let code = std::fs::read_to_string(path)?;
+        self.split(&path.to_string_lossy(), &code)
+    }
+
+    /// Splits the UTF-8 encoded code into chunks
+    pub fn split_bytes(&self, filename: &str, code: &[u8]) -> Result<Vec<Chunk>> {
+        let code = std::str::from_utf8(code)?;
+        self.split(filename, code)
+    }
+
+    /// Returns the language config of the file, the overrides come first
+    pub(crate) fn lang_config(&self, filename: &str, code: &str) -> Option<&'static LangConfig> {
+        let file_ext = Path::new(filename)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("");
+        self.lang_overrides
+            .get(file_ext)
+            .copied()
+            .or_else(|| Lang::detect(filename, code))
+    }
+
+    /// Calls the function with a parser taken from the pool
+    fn with_parser<T>(&self, f: impl FnOnce(&mut Parser) -> T) -> T {
+        let mut parser = self
+            .parsers
+            .lock()
+            .expect("the parser pool is poisoned")
+            .pop()
+            .unwrap_or_default();
+        let result = f(&mut parser);
+        self.parsers
+            .lock()
+            .expect("the parser pool is poisoned")
+            .push(parser);
+        result
+    }
+
+    /// Returns the compiled query of the language, `None` if the language has no query
+    pub(crate) fn query(&self, lang_config: &'static LangConfig) -> Result<Option<Arc<Query>>> {
+        let source = lang_config
+            .lang
+            .iter()
+            .find_map(|lang| self.queries.get(*lang))
+            .map(|query| query.as_str())
+            .unwrap_or(lang_config.query);
+        if source.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(self.query_cache.get(lang_config, source)?))
+    }
+
+    /// Applies the token limit and the hooks to the chunks
+    pub(crate) fn finish(&self, filename: &str, code: &str, chunks: Vec<Chunk>) -> Vec<Chunk> {
+        if self.max_tokens.is_none() && self.hooks.is_empty() {
+            return chunks;
+        }
+        let lines = code.lines().collect::<Vec<&str>>();
+        let mut chunks = match self.max_tokens {
+            Some(max_tokens) => {
+                let mut chunks = token_splitter::limit_tokens(
+                    chunks,
+                    &lines,
+                    self.tokenizer.as_ref(),
+                    max_tokens,
+                );
+                chunk_id::assign_chunk_ids(filename, &lines, &mut chunks);
+                chunks
+            }
+            None => chunks,
+        };
+        for chunk in chunks.iter_mut() {
+            let end = chunk.line_range.end.min(lines.len());
+            let text = lines[chunk.line_range.start.min(end)..end].join("\n");
+            for hook in &self.hooks {
+                hook(filename, &text, chunk);
+            }
+        }
+        chunks
+    }
+}
+
+#[cfg(all(test, feature = "lang-rust"))]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_splitter_is_send_sync() {
+        assert_send_sync::<Splitter>();
+    }
+
+    #[test]
+    fn test_splitter_shared_across_threads() {
+        let splitter = Arc::new(
+            Splitter::builder()
+                .options(SplitOptions {
+                    chunk_line_limit: 40,
+                    ..Default::default()
+                })
+                .build(),
+        );
+        let code = include_str!("../../tests/cases/rust/rust_long_function.rs");
+        let expected = crate::split("rust_long_function.rs", code, splitter.options()).unwrap();
+        let handles = (0..4)
+            .map(|_| {
+                let splitter = splitter.clone();
+                std::thread::spawn(move || splitter.split("rust_long_function.rs", code).unwrap())
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_splitter_max_tokens_and_hooks() {
+        let splitter = Splitter::builder()
+            .options(SplitOptions {
+                chunk_line_limit: 40,
+                ..Default::default()
+            })
+            .max_tokens(100)
+            .hook(|filename, text, chunk| {
+                assert_eq!(filename, "rust_long_function.rs");
+                assert!(!text.is_empty());
+                chunk.entities.retain(|entity| !entity.name.is_empty());
+            })
+            .build();
+        let code = include_str!("../../tests/cases/rust/rust_long_function.rs");
+        let lines = code.lines().collect::<Vec<&str>>();
+        let chunks = splitter.split("rust_long_function.rs", code).unwrap();
+        for chunk in &chunks {
+            let text = lines[chunk.line_range.clone()].join("\n");
+            // a single line may exceed the limit on its own
+            assert!(ApproximateTokenizer.count_tokens(&text) <= 100 || chunk.line_range.len() == 1);
+        }
+        assert!(crate::validate_chunks(code, &chunks).is_empty());
+    }
+
+    #[test]
+    fn test_splitter_custom_query() {
+        let splitter = Splitter::builder()
+            .options(SplitOptions {
+                chunk_line_limit: 40,
+                ..Default::default()
+            })
+            .query(
+                "Rust",
+                "(function_item name: (identifier) @function.name) @function.definition",
+            )
+            .build();
+        let code = include_str!("../../tests/cases/rust/rust_long_function.rs");
+        let chunks = splitter.split("rust_long_function.rs", code).unwrap();
+        let entities = chunks
+            .iter()
+            .flat_map(|chunk| chunk.entities.iter())
+            .collect::<Vec<_>>();
+        assert!(!entities.is_empty());
+        assert!(entities
+            .iter()
+            .all(|entity| entity.entity_type == crate::EntityType::Function));
+    }
+}
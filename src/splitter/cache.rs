@@ -0,0 +1,155 @@
+//
+// cache.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use crate::{
+    lang::LangConfig,
+    Result,
+    SplitError,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{
+        Arc,
+        OnceLock,
+        RwLock,
+    },
+    time::Duration,
+};
+use tree_sitter::{
+    Parser,
+    Query,
+    Tree,
+};
+
+/// The compiled queries, compiled lazily on first use and shared between threads
+#[derive(Default)]
+pub(crate) struct QueryCache {
+    /// the compiled queries keyed by the address of their language config, then by their
+    /// source, a language has a query for its entities, its references and its imports
+    queries: RwLock<HashMap<usize, HashMap<String, Arc<Query>>>>,
+}
+
+impl QueryCache {
+    /// Returns the compiled query of the language config, compiling the query source
+    /// on first use
+    pub(crate) fn get(&self, lang_config: &'static LangConfig, source: &str) -> Result<Arc<Query>> {
+        let key = lang_config as *const LangConfig as usize;
+        if let Some(query) = self
+            .queries
+            .read()
+            .expect("the query cache is poisoned")
+            .get(&key)
+            .and_then(|queries| queries.get(source))
+        {
+            return Ok(query.clone());
+        }
+        let query =
+            Query::new(&(lang_config.grammar)(), source).map_err(|e| SplitError::QueryError {
+                lang: lang_config.name().to_string(),
+                offset: e.offset,
+                kind: e.kind,
+                message: e.message,
+            })?;
+        let query = Arc::new(query);
+        let mut queries = self.queries.write().expect("the query cache is poisoned");
+        Ok(queries
+            .entry(key)
+            .or_default()
+            .entry(source.to_string())
+            .or_insert(query)
+            .clone())
+    }
+}
+
+/// Returns the query cache shared by the whole process
+pub(crate) fn global_query_cache() -> &'static QueryCache {
+    static QUERY_CACHE: OnceLock<QueryCache> = OnceLock::new();
+    QUERY_CACHE.get_or_init(QueryCache::default)
+}
+
+thread_local! {
+    /// the parser reused by every split on the current thread
+    static PARSER: RefCell<Parser> = RefCell::new(Parser::new());
+}
+
+/// Calls the function with the parser of the current thread
+pub(crate) fn with_parser<T>(f: impl FnOnce(&mut Parser) -> T) -> T {
+    PARSER.with(|parser| f(&mut parser.borrow_mut()))
+}
+
+/// Parses the code with the parser of the current thread
+#[cfg(all(
+    test,
+    any(
+        feature = "lang-java",
+        feature = "lang-python",
+        feature = "lang-rust",
+        feature = "lang-solidity",
+        feature = "lang-typescript"
+    )
+))]
+pub(crate) fn parse(lang_config: &LangConfig, code: &str) -> Result<Tree> {
+    with_parser(|parser| parse_with(parser, lang_config, code, None))
+}
+
+/// Parses the code with the given parser, giving up after the timeout
+pub(crate) fn parse_with(
+    parser: &mut Parser,
+    lang_config: &LangConfig,
+    code: &str,
+    timeout: Option<Duration>,
+) -> Result<Tree> {
+    parser
+        .set_language(&(lang_config.grammar)())
+        .map_err(|_| SplitError::IncompatibleGrammar {
+            lang: lang_config.name().to_string(),
+        })?;
+    reparse_with(parser, code, None, timeout)
+}
+
+/// Parses the code with the given parser, reusing the unchanged nodes of the edited
+/// old tree, giving up after the timeout
+pub(crate) fn reparse_with(
+    parser: &mut Parser,
+    code: &str,
+    old_tree: Option<&Tree>,
+    timeout: Option<Duration>,
+) -> Result<Tree> {
+    parser.set_timeout_micros(timeout.map_or(0, |timeout| timeout.as_micros().max(1) as u64));
+    let tree = parser.parse(code, old_tree);
+    parser.set_timeout_micros(0);
+    match (tree, timeout) {
+        (Some(tree), _) => Ok(tree),
+        (None, Some(_)) => {
+            // a timed out parse must be reset before the next one
+            parser.reset();
+            Err(SplitError::Timeout)
+        }
+        (None, None) => Err(SplitError::ParseFailed),
+    }
+}
+
+#[cfg(all(test, feature = "lang-rust"))]
+mod tests {
+    use super::*;
+    use crate::Lang;
+
+    #[test]
+    fn test_query_cache_keyed_by_source() {
+        let lang_config = Lang::from_name("Rust").unwrap();
+        let cache = QueryCache::default();
+        let source = String::from("(function_item) @function");
+        let first = cache.get(lang_config, &source).unwrap();
+        // the same source at another address is the same query
+        let copy = source.clone();
+        let again = cache.get(lang_config, &copy).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        let other = String::from("(struct_item) @struct");
+        let other = cache.get(lang_config, &other).unwrap();
+        assert_eq!(other.capture_names(), &["struct"]);
+    }
+}
@@ -0,0 +1,121 @@
+//
+// chunk_id.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use crate::{
+    Chunk,
+    EntityType,
+};
+use std::collections::HashMap;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// The 64-bit FNV-1a hash, stable across platforms and Rust releases unlike the
+/// hasher of the standard library
+fn fnv1a(bytes: &[u8], mut hash: u64) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Hashes the lines ignoring the leading and trailing whitespaces and the blank lines,
+/// so a re-indented or re-spaced chunk keeps its hash
+pub(crate) fn content_hash(lines: &[&str]) -> String {
+    let mut hash = FNV_OFFSET_BASIS;
+    for line in lines.iter().map(|line| line.trim()) {
+        if line.is_empty() {
+            continue;
+        }
+        hash = fnv1a(line.as_bytes(), hash);
+        hash = fnv1a(b"\n", hash);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Sets the content hash and the id of every chunk
+pub(crate) fn assign_chunk_ids(filename: &str, lines: &[&str], chunks: &mut [Chunk]) {
+    for chunk in chunks.iter_mut() {
+        let end = chunk.line_range.end.min(lines.len());
+        let start = chunk.line_range.start.min(end);
+        chunk.content_hash = content_hash(&lines[start..end]);
+    }
+    assign_ids(filename, chunks);
+}
+
+/// Sets the id of every chunk from its content hash
+///
+/// The id is derived from the path of the file, the qualified path of the entities of
+/// the chunk and the content hash. None of them depends on the line numbers or on the
+/// notebook cell, so a chunk that only moves keeps its id. The imports added by
+/// `SplitOptions::collect_imports` are left out, so the option doesn't change the ids.
+/// Identical chunks of the same file are told apart by their occurrence, the id stays
+/// unique within the file.
+pub(crate) fn assign_ids(filename: &str, chunks: &mut [Chunk]) {
+    let path = filename.replace('\\', "/");
+    let mut occurrences = HashMap::new();
+    for chunk in chunks.iter_mut() {
+        let mut hash = fnv1a(path.as_bytes(), FNV_OFFSET_BASIS);
+        for entity in chunk
+            .entities
+            .iter()
+            .filter(|entity| entity.entity_type != EntityType::Import)
+        {
+            hash = fnv1a(b"\0", hash);
+            if let Some(parent) = &entity.parent {
+                hash = fnv1a(parent.as_bytes(), hash);
+                hash = fnv1a(b"::", hash);
+            }
+            hash = fnv1a(entity.name.as_bytes(), hash);
+        }
+        hash = fnv1a(b"\0", hash);
+        hash = fnv1a(chunk.content_hash.as_bytes(), hash);
+        let occurrence = occurrences.entry(hash).or_insert(0u64);
+        if *occurrence > 0 {
+            hash = fnv1a(b"\0", hash);
+            hash = fnv1a(&occurrence.to_le_bytes(), hash);
+        }
+        *occurrence += 1;
+        chunk.id = format!("{:016x}", hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(vec!["fn main() {", "    run();", "}"], vec!["fn main() {", "", "  run();  ", "}"], true)]
+    #[case(vec!["fn main() {", "    run();", "}"], vec!["fn main() {", "    stop();", "}"], false)]
+    #[case(vec!["a b"], vec!["ab"], false)]
+    fn test_content_hash(#[case] a: Vec<&str>, #[case] b: Vec<&str>, #[case] same: bool) {
+        assert_eq!(content_hash(&a) == content_hash(&b), same);
+    }
+
+    #[test]
+    fn test_identical_chunks_get_unique_ids() {
+        let code = "a\nb\na\nb\na\nb\n";
+        let chunks = crate::splitter::split_lines("test.txt", code, 2);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.content_hash == chunks[0].content_hash));
+        assert_ne!(chunks[0].id, chunks[1].id);
+        assert_ne!(chunks[0].id, chunks[2].id);
+        assert_ne!(chunks[1].id, chunks[2].id);
+        // the first occurrence keeps the id of a unique chunk
+        let unique = crate::splitter::split_lines("test.txt", "a\nb\n", 2);
+        assert_eq!(unique[0].id, chunks[0].id);
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b"", FNV_OFFSET_BASIS), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a", FNV_OFFSET_BASIS), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -11,8 +11,14 @@ use super::{
     EntityType,
     SplitOptions,
 };
-use anyhow::Result;
-use std::collections::HashMap;
+use crate::{
+    Result,
+    SplitError,
+};
+use std::{
+    collections::HashMap,
+    ops::Range,
+};
 use tree_sitter::Node;
 
 /// the capture names for rust function definition
@@ -46,8 +52,12 @@ const ENUM_COMMENT: &str = "enum.comment";
 const ENUM_NAME: &str = "enum.name";
 const ENUM_DERIVE: &str = "enum.derive";
 
+/// the leading lines before the first entity become a chunk of their own only when
+/// they exceed this size and `min_chunk_lines` is not configured
+const DEFAULT_LEADING_CHUNK_LINES: usize = 10;
+
 /// Merge the code entities into code chunks by the given options
-pub(crate) fn merge_code_entities<'a>(
+pub(crate) fn merge_code_entities(
     code: &str,
     entities: &Vec<(CodeEntity, Vec<Node>)>,
     options: &SplitOptions,
@@ -55,26 +65,22 @@ pub(crate) fn merge_code_entities<'a>(
     let lines: Vec<&str> = code.lines().collect();
     let mut chunks = vec![];
     let mut last_chunk_end_line = 0;
+    let min_leading_lines = options
+        .min_chunk_lines
+        .unwrap_or(DEFAULT_LEADING_CHUNK_LINES + 1);
     let mut current_chunk = CodeChunk {
         line_range: 0..0,
         entities: vec![],
     };
     for (i, (entity, nodes)) in entities.iter().enumerate() {
-        let start = if let Some(ref comment_line_range) = &entity.comment_line_range {
-            if comment_line_range.start < entity.body_line_range.start {
-                comment_line_range.start
-            } else {
-                entity.body_line_range.start
-            }
-        } else {
-            entity.body_line_range.start
-        };
+        let start = entity.start_line();
         // skip the entity that is already in the last chunk
         if start < last_chunk_end_line {
             continue;
         }
         let end = entity.body_line_range.end;
-        if i == 0 && start - last_chunk_end_line > 10 {
+        if i == 0 && start > last_chunk_end_line && start - last_chunk_end_line >= min_leading_lines
+        {
             current_chunk.line_range.start = last_chunk_end_line;
             current_chunk.line_range.end = start;
             chunks.push(current_chunk);
@@ -97,12 +103,8 @@ pub(crate) fn merge_code_entities<'a>(
         }
         let entity_lines = end - start;
         if entity_lines > options.chunk_line_limit {
-            let (new_chunks, new_last_chunk_end_line) = super::entity_splitter::split_entity(
-                last_chunk_end_line,
-                &entity,
-                &nodes,
-                options,
-            )?;
+            let (new_chunks, new_last_chunk_end_line) =
+                super::entity_splitter::split_entity(last_chunk_end_line, entity, nodes, options)?;
             chunks.extend(new_chunks);
             last_chunk_end_line = new_last_chunk_end_line;
         } else if entity_lines + left_lines > options.chunk_line_limit {
@@ -127,6 +129,140 @@ pub(crate) fn merge_code_entities<'a>(
     Ok(chunks)
 }
 
+/// Makes the chunks tile the whole file, so every line belongs to exactly one chunk
+///
+/// Out-of-bounds ranges are clamped and overlapping chunks are trimmed. The lines
+/// that no chunk covers are appended to the previous chunk, or become a chunk of
+/// their own when that would exceed the chunk line limit.
+pub(crate) fn tile_chunks(
+    chunks: &mut Vec<CodeChunk>,
+    entities: &[(CodeEntity, Vec<Node>)],
+    line_count: usize,
+    chunk_line_limit: usize,
+) {
+    let mut sorted = std::mem::take(chunks);
+    sorted.sort_by_key(|chunk| chunk.line_range.start);
+    let mut covered = 0;
+    for mut chunk in sorted {
+        let start = chunk.line_range.start.max(covered);
+        let end = chunk.line_range.end.min(line_count);
+        if end <= start {
+            // nothing is left of the chunk, keep its entities in the previous chunk
+            if let Some(last) = chunks.last_mut() {
+                add_entities(last, chunk.entities);
+            }
+            continue;
+        }
+        if start > covered {
+            if chunks.is_empty() && end - covered <= chunk_line_limit {
+                chunk.line_range = covered..end;
+                add_entities(&mut chunk, entities_in(entities, &(covered..start)));
+                covered = end;
+                chunks.push(chunk);
+                continue;
+            }
+            fill_gap(chunks, entities, covered..start, chunk_line_limit);
+        }
+        chunk.line_range = start..end;
+        covered = end;
+        chunks.push(chunk);
+    }
+    if covered < line_count {
+        fill_gap(chunks, entities, covered..line_count, chunk_line_limit);
+    }
+}
+
+/// Appends the uncovered lines to the last chunk or pushes them as a new chunk
+fn fill_gap(
+    chunks: &mut Vec<CodeChunk>,
+    entities: &[(CodeEntity, Vec<Node>)],
+    gap: Range<usize>,
+    chunk_line_limit: usize,
+) {
+    let gap_entities = entities_in(entities, &gap);
+    match chunks.last_mut() {
+        Some(last) if last.line_range.len() + gap.len() <= chunk_line_limit => {
+            last.line_range.end = gap.end;
+            add_entities(last, gap_entities);
+        }
+        _ => chunks.push(CodeChunk {
+            line_range: gap,
+            entities: gap_entities,
+        }),
+    }
+}
+
+/// Returns the entities that intersect the line range
+fn entities_in(entities: &[(CodeEntity, Vec<Node>)], line_range: &Range<usize>) -> Vec<CodeEntity> {
+    entities
+        .iter()
+        .filter(|(entity, _)| {
+            entity.start_line() < line_range.end && entity.body_line_range.end >= line_range.start
+        })
+        .map(|(entity, _)| entity.clone())
+        .collect()
+}
+
+/// Adds the entities missing in the chunk, keeping them ordered by line
+fn add_entities(chunk: &mut CodeChunk, entities: Vec<CodeEntity>) {
+    for entity in entities {
+        if !chunk.entities.contains(&entity) {
+            chunk.entities.push(entity);
+        }
+    }
+    chunk
+        .entities
+        .sort_by_key(|entity| entity.body_line_range.start);
+}
+
+/// Merges every chunk shorter than `min_chunk_lines` into one of its neighbours
+///
+/// The neighbour sharing an entity with the small chunk is preferred, e.g. the
+/// trailing `}` of a split method goes back to the method, otherwise the smaller
+/// neighbour is chosen. The merged chunk may exceed the chunk line limit.
+pub(crate) fn merge_small_chunks(chunks: &mut Vec<CodeChunk>, min_chunk_lines: usize) {
+    let mut i = 0;
+    while chunks.len() > 1 && i < chunks.len() {
+        if chunks[i].line_range.len() >= min_chunk_lines {
+            i += 1;
+            continue;
+        }
+        let shares_entity = |neighbour: &CodeChunk| {
+            chunks[i]
+                .entities
+                .iter()
+                .any(|entity| neighbour.entities.contains(entity))
+        };
+        let target = match (i.checked_sub(1), chunks.get(i + 1)) {
+            (Some(prev), Some(next)) => {
+                let (prev_shared, next_shared) =
+                    (shares_entity(&chunks[prev]), shares_entity(next));
+                if next_shared && !prev_shared {
+                    i + 1
+                } else if prev_shared && !next_shared {
+                    prev
+                } else if next.line_range.len() < chunks[prev].line_range.len() {
+                    i + 1
+                } else {
+                    prev
+                }
+            }
+            (Some(prev), None) => prev,
+            (None, Some(_)) => i + 1,
+            (None, None) => break,
+        };
+        let small_chunk = chunks.remove(i);
+        // the next chunk moves to the position of the removed chunk
+        let target = if target > i { i } else { target };
+        let chunk = &mut chunks[target];
+        chunk.line_range = chunk.line_range.start.min(small_chunk.line_range.start)
+            ..chunk.line_range.end.max(small_chunk.line_range.end);
+        add_entities(chunk, small_chunk.entities);
+        // the merged chunk may still be too small
+        i = target;
+    }
+}
+
 /// Converts the captured nodes to a CodeEntity
 ///
 /// This function processes the captured nodes from the tree-sitter query
@@ -192,7 +328,11 @@ pub(crate) fn convert_node_to_code_entity(
             ENUM_NAME,
             Some(ENUM_DERIVE),
         ),
-        _ => return Err(anyhow::anyhow!("Unsupported entity type")),
+        _ => {
+            return Err(SplitError::InvalidEntity {
+                reason: "Unsupported entity type".to_string(),
+            })
+        }
     };
 
     let comment_line_range = captures
@@ -247,7 +387,9 @@ pub(crate) fn convert_node_to_code_entity(
     let name = captures
         .get(name_key)
         .map(|node| code[node.byte_range.clone()].to_string())
-        .ok_or_else(|| anyhow::anyhow!("Entity name not found"))?;
+        .ok_or_else(|| SplitError::InvalidEntity {
+            reason: "Entity name not found".to_string(),
+        })?;
 
     let (parent_name, interface_names, parent_line_range) = if entity_type == EntityType::Method {
         let parent_name = captures
@@ -277,3 +419,40 @@ pub(crate) fn convert_node_to_code_entity(
     };
     Ok(code_entity)
 }
+
+#[cfg(all(test, feature = "lang-rust"))]
+mod tests {
+    use super::*;
+    use crate::{
+        lang::Lang,
+        splitter::{
+            cache,
+            convert_captures,
+            parse_capture_for_entity,
+        },
+    };
+
+    #[test]
+    fn test_no_empty_leading_chunk() {
+        let code = "fn a() {\n    1;\n}\n\nfn b() {\n    2;\n}\n";
+        let lang_config = Lang::from_name("Rust").unwrap();
+        let tree = cache::parse(lang_config, code).unwrap();
+        let query = cache::global_query_cache()
+            .get(lang_config, lang_config.query)
+            .unwrap();
+        let captures = parse_capture_for_entity(&query, code, &tree).unwrap();
+        let entities = convert_captures(&captures, code);
+        let options = SplitOptions {
+            chunk_line_limit: 4,
+            min_chunk_lines: Some(0),
+            ..Default::default()
+        };
+        let chunks = merge_code_entities(code, &entities, &options).unwrap();
+        let line_ranges = chunks
+            .iter()
+            .map(|chunk| chunk.line_range.clone())
+            .collect::<Vec<Range<usize>>>();
+        // the first entity starts the file, no empty chunk comes before it
+        assert_eq!(line_ranges, vec![0..7]);
+    }
+}
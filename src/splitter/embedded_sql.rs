@@ -0,0 +1,356 @@
+//
+// embedded_sql.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    cache,
+    chunk_id,
+};
+use crate::{
+    diagnostics::SplitResult,
+    lang::{
+        Lang,
+        LangConfig,
+    },
+    Entity,
+    EntityType,
+    Result,
+    SplitError,
+    SplitOptions,
+};
+use std::ops::Range;
+use tree_sitter::{
+    Node,
+    Parser,
+    Tree,
+};
+
+/// the languages whose string literals are searched for SQL
+const HOST_LANGS: &[&str] = &["Python", "TypeScript", "JavaScript"];
+
+/// the comment tagging the next string literal as SQL, e.g. `# language=sql`
+const LANGUAGE_MARKER: &str = "language=sql";
+
+/// the first keyword of a query and a keyword the query must contain, in uppercase,
+/// for the strings detected without a tag
+const SQL_KEYWORDS: &[(&str, &str)] = &[
+    ("SELECT", "FROM"),
+    ("INSERT", "INTO"),
+    ("UPDATE", "SET"),
+    ("DELETE", "FROM"),
+    ("WITH", "AS"),
+    ("CREATE", "TABLE"),
+];
+
+/// A string literal of the host code that may embed SQL
+struct SqlString {
+    /// the byte range of the content of the literal, without its quotes
+    byte_range: Range<usize>,
+    /// the line of the first byte of the content
+    start_line: usize,
+    /// the literal is tagged as SQL by a template tag or a marker comment
+    tagged: bool,
+}
+
+/// Returns true if the string literals of the language are searched for SQL
+pub(crate) fn is_host(lang_config: &LangConfig) -> bool {
+    HOST_LANGS.contains(&lang_config.name())
+}
+
+/// Finds the SQL queries embedded in the string literals of the code and adds every
+/// statement as a `EntityType::Query` entity of the chunks covering it
+///
+/// A literal is SQL when it is tagged with a `sql` template tag or preceded by a
+/// `language=sql` comment, or when it starts with a SQL keyword and parses without
+/// errors. The parent of a query is the innermost entity enclosing it, usually the
+/// function running it.
+pub(crate) fn split_sql_strings(
+    filename: &str,
+    parser: &mut Parser,
+    tree: &Tree,
+    code: &str,
+    options: &SplitOptions,
+    result: &mut SplitResult,
+) -> Result<()> {
+    let Some(sql) = Lang::from_name("SQL") else {
+        return Ok(());
+    };
+    let mut markers = vec![];
+    let mut strings = vec![];
+    collect_strings(&tree.root_node(), code, &mut markers, &mut strings);
+    let mut queries = vec![];
+    for mut string in strings {
+        string.tagged |= markers
+            .iter()
+            .any(|line| *line == string.start_line || *line + 1 == string.start_line);
+        let content = &code[string.byte_range.clone()];
+        if !string.tagged && !looks_like_sql(content) {
+            continue;
+        }
+        let masked = mask_placeholders(content);
+        let sql_tree = match cache::parse_with(parser, sql, &masked, options.limits.parse_timeout) {
+            Ok(tree) => tree,
+            Err(SplitError::Timeout) | Err(SplitError::IncompatibleGrammar { .. }) => continue,
+            Err(e) => return Err(e),
+        };
+        let root = sql_tree.root_node();
+        if !string.tagged && root.has_error() {
+            continue;
+        }
+        let mut cursor = root.walk();
+        for statement in root
+            .children(&mut cursor)
+            .filter(|node| node.kind().ends_with("_statement"))
+        {
+            // the end line is included, like the line ranges of the other entities
+            queries.push((
+                query_name(&statement, content, &masked),
+                statement.start_position().row + string.start_line
+                    ..statement.end_position().row + string.start_line,
+            ));
+        }
+    }
+    if queries.is_empty() {
+        return Ok(());
+    }
+    let entities = result
+        .chunks
+        .iter()
+        .flat_map(|chunk| chunk.entities.iter())
+        .cloned()
+        .collect::<Vec<Entity>>();
+    for (name, line_range) in queries {
+        // the innermost entity enclosing the query
+        let parent = entities
+            .iter()
+            .filter(|entity| {
+                entity.completed_line_range.start <= line_range.start
+                    && entity.completed_line_range.end >= line_range.end
+            })
+            .max_by_key(|entity| entity.completed_line_range.start);
+        for chunk in result.chunks.iter_mut() {
+            if chunk.line_range.start > line_range.end || chunk.line_range.end <= line_range.start {
+                continue;
+            }
+            let chunk_line_range = chunk.line_range.start.max(line_range.start)
+                ..chunk.line_range.end.min(line_range.end);
+            chunk.entities.push(Entity {
+                name: name.clone(),
+                entity_type: EntityType::Query,
+                completed_line_range: line_range.clone(),
+                chunk_line_range,
+                parent: parent.map(|parent| parent.name.clone()),
+                parent_line_range: parent.map(|parent| parent.completed_line_range.clone()),
+            });
+        }
+    }
+    result.diagnostics.line_fallback = false;
+    let lines = code.lines().collect::<Vec<&str>>();
+    chunk_id::assign_chunk_ids(filename, &lines, &mut result.chunks);
+    Ok(())
+}
+
+fn collect_strings(
+    node: &Node,
+    code: &str,
+    markers: &mut Vec<usize>,
+    strings: &mut Vec<SqlString>,
+) {
+    match node.kind() {
+        "comment" => {
+            let text = node.utf8_text(code.as_bytes()).unwrap_or_default();
+            let text = text
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+                .to_lowercase();
+            if text.contains(LANGUAGE_MARKER) {
+                markers.push(node.end_position().row);
+            }
+            return;
+        }
+        "string" | "template_string" => {
+            if let Some(byte_range) = string_content(node) {
+                strings.push(SqlString {
+                    start_line: code[..byte_range.start].matches('\n').count(),
+                    byte_range,
+                    tagged: node.kind() == "template_string" && has_sql_tag(node, code),
+                });
+            }
+            return;
+        }
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_strings(&child, code, markers, strings);
+    }
+}
+
+/// Returns the byte range of the content of a string literal, between its quotes
+fn string_content(node: &Node) -> Option<Range<usize>> {
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor).collect::<Vec<Node>>();
+    // a Python string has its prefix and quotes as children
+    let range = match (
+        children.iter().find(|child| child.kind() == "string_start"),
+        children.iter().find(|child| child.kind() == "string_end"),
+    ) {
+        (Some(start), Some(end)) => start.end_byte()..end.start_byte(),
+        _ => node.start_byte() + 1..node.end_byte().saturating_sub(1),
+    };
+    (range.start < range.end).then_some(range)
+}
+
+/// Returns true if the template string is tagged with `sql`, e.g. sql`SELECT 1` or
+/// Prisma.sql`SELECT 1`
+fn has_sql_tag(node: &Node, code: &str) -> bool {
+    node.parent()
+        .filter(|parent| parent.kind() == "call_expression")
+        .and_then(|parent| parent.child_by_field_name("function"))
+        .and_then(|function| function.utf8_text(code.as_bytes()).ok())
+        .is_some_and(|tag| {
+            let tag = tag.rsplit('.').next().unwrap_or(tag);
+            tag.eq_ignore_ascii_case("sql")
+        })
+}
+
+/// Returns true if the text starts with an uppercase SQL keyword and contains the
+/// keyword expected after it, a lowercase query must be tagged
+fn looks_like_sql(text: &str) -> bool {
+    let words = text
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>();
+    let Some(first) = words.first() else {
+        return false;
+    };
+    SQL_KEYWORDS
+        .iter()
+        .any(|(keyword, expected)| first == keyword && words[1..].contains(expected))
+}
+
+/// Replaces the bind parameters (`?`, `%s`, `:name`, `$1`) and the interpolations
+/// (`{name}`, `${name}`) of a query with an identifier padded to the same length, so
+/// the query parses and keeps its line and byte offsets
+fn mask_placeholders(query: &str) -> String {
+    let mut masked = String::with_capacity(query.len());
+    let mut chars = query.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let end = match c {
+            '?' => start + 1,
+            '%' if chars.peek().is_some_and(|(_, next)| *next == 's') => start + 2,
+            // `::` is a PostgreSQL cast, not a named parameter
+            ':' | '$'
+                if !masked.ends_with(':')
+                    && chars
+                        .peek()
+                        .is_some_and(|(_, next)| next.is_ascii_alphanumeric()) =>
+            {
+                let len = query[start + 1..]
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(query.len() - start - 1);
+                start + 1 + len
+            }
+            '$' | '{' => {
+                let open = if c == '$' { start + 1 } else { start };
+                match query[open..]
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.find('}'))
+                {
+                    Some(close) => open + close + 2,
+                    None => {
+                        masked.push(c);
+                        continue;
+                    }
+                }
+            }
+            _ => {
+                masked.push(c);
+                continue;
+            }
+        };
+        masked.push('x');
+        for c in query[start..end].chars().skip(1) {
+            masked.push(if c == '\n' { '\n' } else { ' ' });
+        }
+        while chars.peek().is_some_and(|(next, _)| *next < end) {
+            chars.next();
+        }
+    }
+    masked
+}
+
+/// Returns the name of a query, its statement and its first table, e.g. `SELECT users`,
+/// a table replaced by a placeholder is left out
+fn query_name(statement: &Node, query: &str, masked: &str) -> String {
+    let keyword = statement
+        .kind()
+        .trim_end_matches("_statement")
+        .replace('_', " ")
+        .to_uppercase();
+    let mut cursor = statement.walk();
+    let from = statement
+        .children(&mut cursor)
+        .find(|child| child.kind() == "from_clause");
+    let table = match from {
+        Some(from) => first_table(&from, true),
+        None => first_table(statement, false),
+    };
+    match table.map(|table| table.byte_range()) {
+        Some(range) if query[range.clone()] == masked[range.clone()] => {
+            format!("{} {}", keyword, &query[range])
+        }
+        _ => keyword,
+    }
+}
+
+/// Returns the first identifier or dotted name under the node, searching the whole
+/// subtree when `deep` is true or only the children otherwise
+fn first_table<'a>(node: &Node<'a>, deep: bool) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor).collect::<Vec<Node<'a>>>();
+    for child in children {
+        if child.kind() == "identifier" || child.kind() == "dotted_name" {
+            return Some(child);
+        }
+        if deep {
+            if let Some(table) = first_table(&child, deep) {
+                return Some(table);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("SELECT id FROM users WHERE id = 1", true)]
+    #[case("\n    INSERT INTO orders (id) VALUES (1)\n", true)]
+    #[case("UPDATE users SET name = 'a'", true)]
+    #[case("update users set name = 'a'", false)]
+    #[case("select a user from the list", false)]
+    #[case("SELECTED FROM the list", false)]
+    #[case("", false)]
+    fn test_looks_like_sql(#[case] text: &str, #[case] expected: bool) {
+        assert_eq!(looks_like_sql(text), expected);
+    }
+
+    #[rstest]
+    #[case("WHERE id = ?", "WHERE id = x")]
+    #[case("WHERE id = %s", "WHERE id = x ")]
+    #[case("WHERE id = :id AND a = $1", "WHERE id = x   AND a = x ")]
+    #[case("FROM {self.table} WHERE", "FROM x            WHERE")]
+    #[case("WHERE id = ${\n  id\n}", "WHERE id = x \n    \n ")]
+    #[case("a::text, '{'", "a::text, '{'")]
+    fn test_mask_placeholders(#[case] query: &str, #[case] expected: &str) {
+        assert_eq!(mask_placeholders(query), expected);
+        assert_eq!(mask_placeholders(query).len(), query.len());
+    }
+}
@@ -3,7 +3,7 @@ use super::{
     CodeEntity,
     SplitOptions,
 };
-use anyhow::Result;
+use crate::Result;
 use std::ops::Range;
 use tree_sitter::Node;
 
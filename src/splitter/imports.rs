@@ -0,0 +1,243 @@
+//
+// imports.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    cache,
+    outline,
+};
+use crate::{
+    lang::{
+        LangConfig,
+        EMPTY_QUERY,
+    },
+    Chunk,
+    Entity,
+    EntityType,
+    Import,
+    Result,
+};
+use std::{
+    collections::HashSet,
+    ops::Range,
+};
+use tree_sitter::{
+    QueryCursor,
+    Tree,
+};
+
+/// An import of the file with the names it binds
+struct FileImport {
+    /// the byte range of the import
+    byte_range: Range<usize>,
+    /// the lines of the import, the end line is included
+    line_range: Range<usize>,
+    /// the names bound by the import, in the order of the code
+    names: Vec<String>,
+}
+
+/// Finds the imports of the code with the import query of the language, adds them as
+/// `EntityType::Import` entities of the chunks covering them and attaches to every
+/// chunk the imports outside it whose names it uses
+///
+/// A name is used by a chunk when one of its identifiers, outside the imports, has the
+/// same text. A wildcard import binds no name, so it is never attached.
+pub(crate) fn collect_imports(
+    lang_config: &'static LangConfig,
+    tree: &Tree,
+    code: &str,
+    chunks: &mut [Chunk],
+) -> Result<()> {
+    if lang_config.import_query == EMPTY_QUERY {
+        return Ok(());
+    }
+    let query = cache::global_query_cache().get(lang_config, lang_config.import_query)?;
+    let mut imports = vec![];
+    let mut names = vec![];
+    let mut query_cursor = QueryCursor::new();
+    for m in query_cursor.matches(&query, tree.root_node(), code.as_bytes()) {
+        for c in m.captures {
+            let Ok(text) = c.node.utf8_text(code.as_bytes()) else {
+                continue;
+            };
+            match query.capture_names()[c.index as usize] {
+                "import.statement" => imports.push(FileImport {
+                    byte_range: c.node.byte_range(),
+                    line_range: c.node.start_position().row..c.node.end_position().row,
+                    names: vec![],
+                }),
+                "import.name" => names.push((c.node.start_byte(), text.to_string())),
+                "import.path" => names.push((c.node.start_byte(), package_name(text))),
+                _ => {}
+            }
+        }
+    }
+    if imports.is_empty() {
+        return Ok(());
+    }
+    imports.sort_by_key(|import| import.byte_range.start);
+    names.sort();
+    for (start_byte, name) in names {
+        // the innermost import binding the name
+        if let Some(import) = imports
+            .iter_mut()
+            .filter(|import| import.byte_range.contains(&start_byte))
+            .last()
+        {
+            if !import.names.contains(&name) {
+                import.names.push(name);
+            }
+        }
+    }
+    // the identifiers of every line outside the imports
+    let mut line_identifiers = vec![vec![]; tree.root_node().end_position().row + 1];
+    let mut import_lines = vec![false; line_identifiers.len()];
+    for import in &imports {
+        import_lines[import.line_range.start..=import.line_range.end].fill(true);
+    }
+    for (name, line) in outline::identifiers(tree, code) {
+        if !import_lines[line] {
+            line_identifiers[line].push(name);
+        }
+    }
+    for chunk in chunks.iter_mut() {
+        let end = chunk.line_range.end.min(line_identifiers.len());
+        let start = chunk.line_range.start.min(end);
+        let used = line_identifiers[start..end]
+            .iter()
+            .flatten()
+            .map(|name| name.as_str())
+            .collect::<HashSet<&str>>();
+        chunk.imports = vec![];
+        for import in &imports {
+            let statement = &code[import.byte_range.clone()];
+            if chunk.line_range.start > import.line_range.end
+                || chunk.line_range.end <= import.line_range.start
+            {
+                let names = import
+                    .names
+                    .iter()
+                    .filter(|name| used.contains(name.as_str()))
+                    .cloned()
+                    .collect::<Vec<String>>();
+                if !names.is_empty() {
+                    chunk.imports.push(Import {
+                        statement: statement.to_string(),
+                        names,
+                        line_range: import.line_range.clone(),
+                    });
+                }
+                continue;
+            }
+            let entity = Entity {
+                name: statement
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+                    .join(" "),
+                entity_type: EntityType::Import,
+                completed_line_range: import.line_range.clone(),
+                chunk_line_range: chunk.line_range.start.max(import.line_range.start)
+                    ..chunk.line_range.end.min(import.line_range.end),
+                parent: None,
+                parent_line_range: None,
+            };
+            // the entities stay in the order of the code
+            let index = chunk.entities.partition_point(|other| {
+                other.completed_line_range.start <= entity.completed_line_range.start
+            });
+            chunk.entities.insert(index, entity);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name a Go import path binds, the last segment of the path skipping a
+/// major version suffix, e.g. `y` for `github.com/x/y/v2`
+fn package_name(path: &str) -> String {
+    let mut segments = path.trim_matches('"').rsplit('/');
+    let last = segments.next().unwrap_or_default();
+    let is_version = last.len() > 1
+        && last.starts_with('v')
+        && last[1..].bytes().all(|byte| byte.is_ascii_digit());
+    match segments.next() {
+        Some(parent) if is_version => parent.to_string(),
+        _ => last.to_string(),
+    }
+}
+
+#[cfg(all(test, feature = "lang-rust"))]
+mod tests {
+    use super::*;
+    use crate::{
+        split,
+        SplitOptions,
+    };
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("\"fmt\"", "fmt")]
+    #[case("\"net/http\"", "http")]
+    #[case("\"github.com/x/y/v2\"", "y")]
+    #[case("\"github.com/x/v2\"", "x")]
+    #[case("\"github.com/x/vendor\"", "vendor")]
+    #[case("\"v2\"", "v2")]
+    fn test_package_name(#[case] path: &str, #[case] name: &str) {
+        assert_eq!(package_name(path), name);
+    }
+
+    #[test]
+    fn test_collect_rust_imports() {
+        let code = r#"use std::{
+    collections::HashMap,
+    fmt::Display as Show,
+};
+use std::io::*;
+
+fn count(words: &[&str]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in words {
+        *counts.entry(word.to_string()).or_default() += 1;
+    }
+    counts
+}
+
+fn show(value: &dyn Show) -> String {
+    value.to_string()
+}
+"#;
+        let options = SplitOptions {
+            chunk_line_limit: 6,
+            collect_imports: true,
+            ..Default::default()
+        };
+        let chunks = split("test.rs", code, &options).unwrap();
+        assert_eq!(chunks.len(), 2);
+        let imports = chunks[0]
+            .entities
+            .iter()
+            .filter(|entity| entity.entity_type == EntityType::Import)
+            .map(|entity| (entity.name.as_str(), entity.completed_line_range.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            imports,
+            vec![
+                (
+                    "use std::{ collections::HashMap, fmt::Display as Show, };",
+                    0..3
+                ),
+                ("use std::io::*;", 4..4),
+            ]
+        );
+        // the chunk holding the imports doesn't need them as context
+        assert!(chunks[0].imports.is_empty());
+        let attached = chunks[1]
+            .imports
+            .iter()
+            .map(|import| (import.names.clone(), import.line_range.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(attached, vec![(vec!["Show".to_string()], 0..3)]);
+        assert!(chunks[1].imports[0].statement.starts_with("use std::{\n"));
+    }
+}
@@ -0,0 +1,526 @@
+//
+// incremental.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    cache,
+    exceeded_input_limit,
+    global_query,
+    limit_error,
+    split_generated,
+    split_parsed,
+    tree_depth_exceeds,
+};
+use crate::{
+    lang::{
+        Lang,
+        LangConfig,
+    },
+    rechunk::{
+        diff_chunks,
+        hunk::Hunk,
+    },
+    Chunk,
+    Limit,
+    Result,
+    SplitError,
+    SplitOptions,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use tree_sitter::{
+    InputEdit,
+    Parser,
+    Point,
+    Tree,
+};
+
+/// The difference between the chunks before and after an edit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChunkDiff {
+    /// the new chunks, with their line ranges in the new code
+    pub added: Vec<Chunk>,
+    /// the chunks that are gone, with their line ranges in the previous code
+    pub removed: Vec<Chunk>,
+    /// the chunks kept from the previous split, moved to their line ranges in the new code
+    pub unchanged: Vec<Chunk>,
+}
+
+/// Splits a file that is edited over time, keeping the syntax tree and the chunks
+/// of the previous version
+///
+/// Every edit reparses the code incrementally, reusing the unchanged nodes of the
+/// previous tree, and splits the new tree like `split`, so the chunks are the ones of
+/// a full split of the new code, with the imports, the references, the code blocks,
+/// the embedded queries and the generated file policy of the options. The chunks of
+/// the previous version that only moved to other lines are reported as unchanged.
+///
+/// Exceeding a limit of the options always returns an error, as the syntax tree is
+/// needed to split the next version. A failed edit leaves the splitter on the previous
+/// version.
+///
+/// # Example
+///
+/// ```
+/// use devgen_splitter::{
+///     IncrementalSplitter,
+///     SplitOptions,
+/// };
+///
+/// let options = SplitOptions {
+///     chunk_line_limit: 5,
+///     ..Default::default()
+/// };
+/// let code = "fn main() {\n    println!(\"Hello, world!\");\n}\n";
+/// let mut splitter = IncrementalSplitter::new("example.rs", code, &options).unwrap();
+/// let diff = splitter
+///     .update("fn main() {\n    println!(\"Hello, devgen!\");\n}\n")
+///     .unwrap();
+/// assert_eq!(diff.removed.len(), 1);
+/// ```
+pub struct IncrementalSplitter {
+    filename: String,
+    lang_config: &'static LangConfig,
+    options: SplitOptions,
+    parser: Parser,
+    code: String,
+    tree: Tree,
+    chunks: Vec<Chunk>,
+}
+
+impl IncrementalSplitter {
+    /// Splits the first version of the code, the language is detected from the filename
+    /// and the code, see `Lang::detect`
+    pub fn new(filename: &str, code: &str, options: &SplitOptions) -> Result<Self> {
+        let Some(lang_config) = Lang::detect(filename, code) else {
+            return Err(SplitError::UnsupportedLanguage {
+                filename: filename.to_string(),
+            });
+        };
+        options.validate()?;
+        if let Some(limit) = exceeded_input_limit(code, &options.limits) {
+            return Err(limit_error(limit));
+        }
+        let mut parser = Parser::new();
+        let tree = cache::parse_with(&mut parser, lang_config, code, options.limits.parse_timeout)?;
+        check_tree_depth(&tree, options)?;
+        let chunks = split_new_tree(filename, lang_config, &tree, code, options)?;
+        Ok(Self {
+            filename: filename.to_string(),
+            lang_config,
+            options: options.clone(),
+            parser,
+            code: code.to_string(),
+            tree,
+            chunks,
+        })
+    }
+
+    /// Returns the chunks of the current code
+    pub fn chunks(&self) -> &[Chunk] {
+        &self.chunks
+    }
+
+    /// Returns the current code
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns the syntax tree of the current code
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Replaces the code with a new version, the edit is computed from the common
+    /// prefix and suffix of the two versions
+    pub fn update(&mut self, new_code: &str) -> Result<ChunkDiff> {
+        match text_edit(&self.code, new_code) {
+            Some(edit) => self.edit(&[edit], new_code),
+            None => Ok(ChunkDiff {
+                unchanged: self.chunks.clone(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Applies the edits, in the order they were made, and splits the new code
+    ///
+    /// # Arguments
+    ///
+    /// * `edits` - The edits that turn the current code into the new code, as passed to
+    ///   `Tree::edit`.
+    /// * `new_code` - The code after the edits.
+    pub fn edit(&mut self, edits: &[InputEdit], new_code: &str) -> Result<ChunkDiff> {
+        if let Some(limit) = exceeded_input_limit(new_code, &self.options.limits) {
+            return Err(limit_error(limit));
+        }
+        // the state is only replaced once the new code is split, a failed edit keeps
+        // the previous version
+        let mut old_tree = self.tree.clone();
+        for edit in edits {
+            old_tree.edit(edit);
+        }
+        let new_tree = cache::reparse_with(
+            &mut self.parser,
+            new_code,
+            Some(&old_tree),
+            self.options.limits.parse_timeout,
+        )?;
+        check_tree_depth(&new_tree, &self.options)?;
+        let chunks = split_new_tree(
+            &self.filename,
+            self.lang_config,
+            &new_tree,
+            new_code,
+            &self.options,
+        )?;
+        let diff = diff_chunks(
+            chunks.clone(),
+            &changed_lines(&self.code, new_code),
+            &self.chunks,
+        );
+        self.chunks = chunks;
+        self.code = new_code.to_string();
+        self.tree = new_tree;
+        Ok(diff)
+    }
+}
+
+/// Splits the parsed code with the same steps as `split`
+fn split_new_tree(
+    filename: &str,
+    lang_config: &'static LangConfig,
+    tree: &Tree,
+    code: &str,
+    options: &SplitOptions,
+) -> Result<Vec<Chunk>> {
+    let result = split_generated(filename, code, options, || {
+        // the parser of the splitter keeps its language for the next reparse, the code
+        // blocks and the embedded queries are parsed by the parser of the thread
+        cache::with_parser(|parser| {
+            split_parsed(
+                filename,
+                parser,
+                lang_config,
+                tree,
+                &global_query,
+                code,
+                options,
+            )
+        })
+    })?;
+    Ok(result.chunks)
+}
+
+fn check_tree_depth(tree: &Tree, options: &SplitOptions) -> Result<()> {
+    match options.limits.max_tree_depth {
+        Some(max_tree_depth) if tree_depth_exceeds(tree, max_tree_depth) => {
+            Err(limit_error(Limit::TreeDepth))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the lines changed between the two versions of the code, found from their
+/// common leading and trailing lines
+fn changed_lines(old_code: &str, new_code: &str) -> Vec<Hunk> {
+    let old_lines = old_code.lines().collect::<Vec<&str>>();
+    let new_lines = new_code.lines().collect::<Vec<&str>>();
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let hunk = Hunk {
+        old_line_range: prefix..old_lines.len() - suffix,
+        new_line_range: prefix..new_lines.len() - suffix,
+    };
+    match hunk.old_line_range.is_empty() && hunk.new_line_range.is_empty() {
+        // nothing changed but the trailing newline
+        true => vec![],
+        false => vec![hunk],
+    }
+}
+
+/// Returns the position of the byte offset in the code
+fn point_at(code: &str, byte: usize) -> Point {
+    let before = &code.as_bytes()[..byte];
+    let row = before.iter().filter(|b| **b == b'\n').count();
+    let line_start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    Point::new(row, byte - line_start)
+}
+
+/// Computes the edit turning the old code into the new code from their common
+/// prefix and suffix, `None` if the two versions are the same
+fn text_edit(old: &str, new: &str) -> Option<InputEdit> {
+    if old == new {
+        return None;
+    }
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(old, new)| old == new)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let mut suffix = old.as_bytes()[prefix..]
+        .iter()
+        .rev()
+        .zip(new.as_bytes()[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    let old_end_byte = old.len() - suffix;
+    let new_end_byte = new.len() - suffix;
+    Some(InputEdit {
+        start_byte: prefix,
+        old_end_byte,
+        new_end_byte,
+        start_position: point_at(old, prefix),
+        old_end_position: point_at(old, old_end_byte),
+        new_end_position: point_at(new, new_end_byte),
+    })
+}
+
+#[cfg(all(test, feature = "lang-rust"))]
+mod tests {
+    use super::*;
+    use crate::{
+        split,
+        splitter::ranges::shift_chunk,
+        validate_chunks,
+    };
+    use std::ops::Range;
+
+    fn options() -> SplitOptions {
+        SplitOptions {
+            chunk_line_limit: 20,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_text_edit() {
+        let edit = text_edit("fn a() {}\nfn b() {}\n", "fn a() {}\nfn bc() {}\n").unwrap();
+        assert_eq!(edit.start_byte, 14);
+        assert_eq!(edit.old_end_byte, 14);
+        assert_eq!(edit.new_end_byte, 15);
+        assert_eq!(edit.start_position, Point::new(1, 4));
+        assert_eq!(edit.new_end_position, Point::new(1, 5));
+        assert!(text_edit("fn a() {}", "fn a() {}").is_none());
+    }
+
+    #[test]
+    fn test_update_keeps_unaffected_chunks() {
+        let code = include_str!("../../tests/cases/rust/rust_similar_case.rs");
+        let mut splitter = IncrementalSplitter::new("lib.rs", code, &options()).unwrap();
+        let before = splitter.chunks().to_vec();
+        // insert two lines in the body of `impl<T: Clone> Change<T>`, lines 81..120
+        let mut new_lines = code.lines().collect::<Vec<&str>>();
+        new_lines.insert(100, "    // a new comment");
+        new_lines.insert(100, "    // another new comment");
+        let new_code = new_lines.join("\n") + "\n";
+        let diff = splitter.update(&new_code).unwrap();
+        assert!(!diff.removed.is_empty());
+        assert!(!diff.added.is_empty());
+        assert_eq!(
+            diff.unchanged.len() + diff.added.len(),
+            splitter.chunks().len()
+        );
+        assert!(validate_chunks(&new_code, splitter.chunks()).is_empty());
+        for chunk in before.iter().filter(|chunk| chunk.line_range.end <= 71) {
+            assert!(diff.unchanged.contains(chunk));
+        }
+        for chunk in before.iter().filter(|chunk| chunk.line_range.start >= 186) {
+            assert!(diff.unchanged.contains(&shift_chunk(chunk.clone(), 2)));
+        }
+    }
+
+    #[test]
+    fn test_edit_with_input_edits() {
+        let code = include_str!("../../tests/cases/rust/rust_function_in_mod.rs");
+        let mut splitter = IncrementalSplitter::new("lib.rs", code, &options()).unwrap();
+        let new_code = format!("fn added() {{}}\n{}", code);
+        let edit = InputEdit {
+            start_byte: 0,
+            old_end_byte: 0,
+            new_end_byte: 15,
+            start_position: Point::new(0, 0),
+            old_end_position: Point::new(0, 0),
+            new_end_position: Point::new(1, 0),
+        };
+        splitter.edit(&[edit], &new_code).unwrap();
+        assert!(validate_chunks(&new_code, splitter.chunks()).is_empty());
+        assert!(splitter
+            .chunks()
+            .iter()
+            .flat_map(|chunk| chunk.entities.iter())
+            .any(|entity| entity.name == "added"));
+        assert_eq!(splitter.code(), new_code);
+    }
+
+    #[test]
+    fn test_update_removed_lines_and_no_change() {
+        let code = include_str!("../../tests/cases/rust/rust_long_function.rs");
+        let mut splitter = IncrementalSplitter::new("lib.rs", code, &options()).unwrap();
+        let diff = splitter.update(code).unwrap();
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.unchanged, splitter.chunks());
+        let lines = code.lines().collect::<Vec<&str>>();
+        let new_code = lines[..lines.len() - 3].join("\n");
+        splitter.update(&new_code).unwrap();
+        assert!(validate_chunks(&new_code, splitter.chunks()).is_empty());
+    }
+
+    #[test]
+    fn test_failed_edit_keeps_the_state() {
+        let code = "fn a() {\n    1;\n}\n\nfn b() {\n    2;\n}\n\nfn c() {\n    3;\n}\n";
+        let options = SplitOptions {
+            chunk_line_limit: 4,
+            limits: crate::SplitLimits {
+                max_tree_depth: Some(20),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut splitter = IncrementalSplitter::new("lib.rs", code, &options).unwrap();
+        let too_deep = code.replace(
+            "    2;",
+            &format!("    {}2{};", "(".repeat(30), ")".repeat(30)),
+        );
+        assert!(matches!(
+            splitter.update(&too_deep),
+            Err(SplitError::LimitExceeded {
+                limit: Limit::TreeDepth
+            })
+        ));
+        // the tree is not edited by the failed edit
+        assert_eq!(splitter.code(), code);
+        assert!(!splitter.tree().root_node().has_changes());
+        assert_eq!(splitter.tree().root_node().byte_range(), 0..code.len());
+        // the next edit is applied to the previous version
+        let new_code = code.replace(
+            "fn c() {\n    3;",
+            "fn c() {\n    let value = 3;\n    value;",
+        );
+        splitter.update(&new_code).unwrap();
+        let fresh = cache::parse(splitter.lang_config, &new_code).unwrap();
+        assert_eq!(
+            splitter.tree().root_node().to_sexp(),
+            fresh.root_node().to_sexp()
+        );
+        let ranges = |tree: &Tree| {
+            let mut cursor = tree.walk();
+            tree.root_node()
+                .children(&mut cursor)
+                .map(|node| node.byte_range())
+                .collect::<Vec<Range<usize>>>()
+        };
+        assert_eq!(ranges(splitter.tree()), ranges(&fresh));
+        assert!(validate_chunks(&new_code, splitter.chunks()).is_empty());
+    }
+
+    /// Checks that the chunks of the edited code are the ones of a full split and that
+    /// the diff covers them
+    fn assert_edit_matches_split(
+        filename: &str,
+        code: &str,
+        new_code: &str,
+        options: &SplitOptions,
+    ) {
+        let mut splitter = IncrementalSplitter::new(filename, code, options).unwrap();
+        assert_eq!(splitter.chunks(), split(filename, code, options).unwrap());
+        let diff = splitter.update(new_code).unwrap();
+        let expected = split(filename, new_code, options).unwrap();
+        assert_eq!(splitter.chunks(), expected);
+        let mut chunks = diff
+            .unchanged
+            .iter()
+            .chain(diff.added.iter())
+            .cloned()
+            .collect::<Vec<Chunk>>();
+        chunks.sort_by_key(|chunk| (chunk.line_range.start, chunk.line_range.end));
+        assert_eq!(chunks, expected);
+        assert!(!diff.unchanged.is_empty());
+    }
+
+    #[test]
+    fn test_edit_matches_split() {
+        let code = r#"// @generated
+use std::collections::HashMap;
+
+fn first() -> HashMap<String, usize> {
+    HashMap::new()
+}
+
+fn second() -> HashMap<String, usize> {
+    let counts = first();
+    counts
+}
+"#;
+        let options = SplitOptions {
+            chunk_line_limit: 5,
+            collect_references: true,
+            collect_imports: true,
+            generated: crate::GeneratedPolicy::Tag,
+            ..Default::default()
+        };
+        let new_code = code.replace("    counts\n", "    // a new line\n    counts\n");
+        assert_edit_matches_split("lib.rs", code, &new_code, &options);
+        let chunks = split("lib.rs", &new_code, &options).unwrap();
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.generated && !chunk.references.is_empty()));
+        assert!(!chunks[1].imports.is_empty());
+    }
+
+    #[cfg(all(feature = "lang-markdown", feature = "lang-python"))]
+    #[test]
+    fn test_edit_matches_split_code_blocks() {
+        let code = include_str!("../../tests/cases/md/test_code_blocks.md");
+        let options = SplitOptions {
+            chunk_line_limit: 10,
+            ..Default::default()
+        };
+        let new_code = code.replace("        self.url = url", "        self.url = url.strip()");
+        assert_edit_matches_split("guide.md", code, &new_code, &options);
+        assert!(split("guide.md", &new_code, &options)
+            .unwrap()
+            .iter()
+            .any(|chunk| chunk.lang.as_deref() == Some("Python")));
+    }
+
+    #[cfg(all(feature = "lang-python", feature = "lang-sql"))]
+    #[test]
+    fn test_edit_matches_split_embedded_sql() {
+        let code = include_str!("../../tests/cases/sql_embedded/repository.py");
+        let options = SplitOptions {
+            chunk_line_limit: 10,
+            embedded_sql: true,
+            ..Default::default()
+        };
+        let new_code = code.replace("    print(message)", "    print(message.strip())");
+        assert_edit_matches_split("repository.py", code, &new_code, &options);
+        assert!(split("repository.py", &new_code, &options)
+            .unwrap()
+            .iter()
+            .flat_map(|chunk| chunk.entities.iter())
+            .any(|entity| entity.entity_type == crate::EntityType::Query));
+    }
+}
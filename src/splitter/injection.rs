@@ -0,0 +1,304 @@
+//
+// injection.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    cache,
+    chunk_id,
+    ranges::{
+        shift_chunk,
+        shift_range,
+    },
+    split_tree,
+    QueryResolver,
+};
+use crate::{
+    diagnostics::SplitResult,
+    lang::Lang,
+    Chunk,
+    Result,
+    SplitError,
+    SplitOptions,
+};
+use std::ops::Range;
+use tree_sitter::{
+    Node,
+    Parser,
+    Tree,
+};
+
+/// A fenced code block of a Markdown file
+struct CodeBlock {
+    /// the language tag of the block, e.g. `rust` for ```` ```rust ````
+    tag: String,
+    /// the lines of the block, including the fences
+    line_range: Range<usize>,
+    /// the lines between the fences
+    content_line_range: Range<usize>,
+    /// the byte offset of the first content line
+    content_start_byte: usize,
+}
+
+/// A heading of a Markdown file
+struct Heading {
+    line: usize,
+    level: usize,
+    text: String,
+}
+
+/// Splits the fenced code blocks of a Markdown file by the language of their tag, in
+/// place of the chunks of the Markdown lines
+///
+/// The chunks of a block carry its language and the headings enclosing it, their
+/// line ranges and the line ranges of their entities are those of the Markdown file.
+/// The first chunk of a block starts at the opening fence and the last one ends at
+/// the closing fence. A block with an unknown tag, or that can't be parsed in time,
+/// stays in the Markdown chunks.
+pub(crate) fn split_code_blocks(
+    filename: &str,
+    parser: &mut Parser,
+    tree: &Tree,
+    code: &str,
+    queries: &QueryResolver,
+    options: &SplitOptions,
+    result: &mut SplitResult,
+) -> Result<()> {
+    let lines = code.lines().collect::<Vec<&str>>();
+    let mut blocks = vec![];
+    let mut headings = vec![];
+    collect_blocks(&tree.root_node(), code, &lines, &mut blocks, &mut headings);
+    for block in blocks.iter_mut() {
+        block.line_range.end = block.line_range.end.min(lines.len());
+        block.content_line_range.end = block.content_line_range.end.min(lines.len());
+        block.content_line_range.start = block
+            .content_line_range
+            .start
+            .min(block.content_line_range.end);
+    }
+    let mut injected: Vec<(Range<usize>, Vec<Chunk>)> = vec![];
+    for block in blocks {
+        let Some(lang_config) = Lang::from_name(&block.tag) else {
+            continue;
+        };
+        if lang_config.name() == "Markdown" || block.content_line_range.is_empty() {
+            continue;
+        }
+        let content = lines[block.content_line_range.clone()].join("\n");
+        let block_tree =
+            match cache::parse_with(parser, lang_config, &content, options.limits.parse_timeout) {
+                Ok(tree) => tree,
+                Err(SplitError::Timeout) | Err(SplitError::IncompatibleGrammar { .. }) => continue,
+                Err(e) => return Err(e),
+            };
+        let query = queries(lang_config)?;
+        let block_result = split_tree(
+            filename,
+            lang_config,
+            &block_tree,
+            query.as_deref(),
+            &content,
+            options,
+        )?;
+        if block_result.chunks.is_empty() {
+            continue;
+        }
+        let offset = block.content_line_range.start;
+        let context = enclosing_headings(&headings, block.line_range.start);
+        let mut chunks = block_result
+            .chunks
+            .into_iter()
+            .map(|chunk| {
+                let mut chunk = shift_chunk(chunk, offset as isize);
+                chunk.lang = Some(lang_config.name().to_string());
+                chunk.headings = context.clone();
+                chunk
+            })
+            .collect::<Vec<Chunk>>();
+        if let Some(first) = chunks.first_mut() {
+            first.line_range.start = block.line_range.start;
+        }
+        if let Some(last) = chunks.last_mut() {
+            last.line_range.end = block.line_range.end;
+        }
+        for mut error in block_result.diagnostics.syntax_errors {
+            error.line_range = shift_range(&error.line_range, offset as isize);
+            error.byte_range = error.byte_range.start + block.content_start_byte
+                ..error.byte_range.end + block.content_start_byte;
+            result.diagnostics.syntax_errors.push(error);
+        }
+        injected.push((block.line_range, chunks));
+    }
+    if injected.is_empty() {
+        return Ok(());
+    }
+    let mut chunks = vec![];
+    for chunk in result.chunks.drain(..) {
+        let mut rest = chunk.line_range.clone();
+        for (line_range, _) in &injected {
+            if line_range.start >= rest.end || line_range.end <= rest.start {
+                continue;
+            }
+            if rest.start < line_range.start {
+                chunks.push(Chunk {
+                    line_range: rest.start..line_range.start,
+                    ..chunk.clone()
+                });
+            }
+            rest.start = line_range.end.max(rest.start);
+        }
+        if !rest.is_empty() {
+            chunks.push(Chunk {
+                line_range: rest,
+                ..chunk
+            });
+        }
+    }
+    chunks.extend(injected.into_iter().flat_map(|(_, chunks)| chunks));
+    chunks.sort_by_key(|chunk| (chunk.line_range.start, chunk.line_range.end));
+    chunk_id::assign_chunk_ids(filename, &lines, &mut chunks);
+    result.diagnostics.line_fallback = chunks.iter().all(|chunk| chunk.entities.is_empty());
+    result.chunks = chunks;
+    Ok(())
+}
+
+fn collect_blocks(
+    node: &Node,
+    code: &str,
+    lines: &[&str],
+    blocks: &mut Vec<CodeBlock>,
+    headings: &mut Vec<Heading>,
+) {
+    match node.kind() {
+        "fenced_code_block" => {
+            if let Some(block) = code_block(node, code) {
+                blocks.push(block);
+            }
+            return;
+        }
+        "atx_heading" | "setext_heading" => {
+            if let Some(heading) = heading(node, lines) {
+                headings.push(heading);
+            }
+            return;
+        }
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_blocks(&child, code, lines, blocks, headings);
+    }
+}
+
+fn code_block(node: &Node, code: &str) -> Option<CodeBlock> {
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor).collect::<Vec<Node>>();
+    let tag = children
+        .iter()
+        .find(|child| child.kind() == "info_string")
+        .and_then(|info| info.utf8_text(code.as_bytes()).ok())
+        .and_then(|info| info.split_whitespace().next())
+        // e.g. `rust,ignore` in the Rust documentation
+        .and_then(|tag| tag.split(',').next())
+        .map(|tag| tag.trim_matches(|c: char| c == '{' || c == '}' || c == '.'))
+        .filter(|tag| !tag.is_empty())?
+        .to_string();
+    let open = node.start_position().row;
+    let delimiters = children
+        .iter()
+        .filter(|child| child.kind() == "fenced_code_block_delimiter")
+        .collect::<Vec<&Node>>();
+    // an unclosed block runs to the end of its node
+    let (close, end) = match delimiters.get(1) {
+        Some(delimiter) => (
+            delimiter.start_position().row,
+            delimiter.start_position().row + 1,
+        ),
+        None => {
+            let end = node.end_position();
+            let end = if end.column == 0 {
+                end.row
+            } else {
+                end.row + 1
+            };
+            (end, end)
+        }
+    };
+    let content_start_byte = children
+        .iter()
+        .find(|child| child.kind() == "code_fence_content")
+        .map(|content| content.start_byte())
+        .unwrap_or(node.end_byte());
+    Some(CodeBlock {
+        tag: tag.to_lowercase(),
+        line_range: open..end,
+        content_line_range: (open + 1).min(close)..close,
+        content_start_byte,
+    })
+}
+
+fn heading(node: &Node, lines: &[&str]) -> Option<Heading> {
+    let line = node.start_position().row;
+    let text = lines.get(line)?.trim();
+    let (level, text) = if node.kind() == "atx_heading" {
+        let level = text.chars().take_while(|c| *c == '#').count();
+        (level, text[level..].trim().trim_end_matches('#').trim())
+    } else {
+        let underline = lines.get(node.end_position().row.saturating_sub(1))?;
+        let level = if underline.trim_start().starts_with('=') {
+            1
+        } else {
+            2
+        };
+        (level, text)
+    };
+    Some(Heading {
+        line,
+        level,
+        text: text.to_string(),
+    })
+}
+
+/// Returns the texts of the headings enclosing the line, outermost first
+fn enclosing_headings(headings: &[Heading], line: usize) -> Vec<String> {
+    let mut stack: Vec<&Heading> = vec![];
+    for heading in headings.iter().take_while(|heading| heading.line < line) {
+        while stack.last().is_some_and(|last| last.level >= heading.level) {
+            stack.pop();
+        }
+        stack.push(heading);
+    }
+    stack.iter().map(|heading| heading.text.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn heading(line: usize, level: usize, text: &str) -> Heading {
+        Heading {
+            line,
+            level,
+            text: text.to_string(),
+        }
+    }
+
+    #[rstest]
+    #[case(0, vec![])]
+    #[case(3, vec!["Guide"])]
+    #[case(6, vec!["Guide", "Install"])]
+    #[case(12, vec!["Guide", "Usage", "Rust"])]
+    #[case(20, vec!["Guide", "API"])]
+    fn test_enclosing_headings(#[case] line: usize, #[case] expected: Vec<&str>) {
+        let headings = vec![
+            heading(0, 1, "Guide"),
+            heading(4, 2, "Install"),
+            heading(8, 2, "Usage"),
+            heading(10, 3, "Rust"),
+            heading(15, 2, "API"),
+        ];
+        assert_eq!(enclosing_headings(&headings, line), expected);
+    }
+}
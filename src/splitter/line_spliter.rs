@@ -1,14 +1,14 @@
-use crate::Chunk;
-use anyhow::Result;
+use super::CodeChunk;
+use crate::Result;
 use std::ops::Range;
 use tree_sitter::Node;
 
 pub fn split_tree_node(
-    lines: &Vec<&str>,
+    lines: &[&str],
     node: &Node,
     max_lines_per_chunk: usize,
     min_lines_per_chunk: usize,
-) -> Result<Vec<Chunk>> {
+) -> Result<Vec<CodeChunk>> {
     let mut chunks: Vec<Range<usize>> = Vec::new();
     let last_chunk_end_line_number = chunk_by_lines(
         node,
@@ -18,20 +18,24 @@ pub fn split_tree_node(
         min_lines_per_chunk,
     )?;
 
-    if last_chunk_end_line_number < lines.len()
-        && lines.len() - last_chunk_end_line_number > min_lines_per_chunk
-    {
-        chunks.push(Range {
-            start: last_chunk_end_line_number,
-            end: lines.len(),
-        });
+    if last_chunk_end_line_number < lines.len() {
+        // a short trailing segment is appended to the last chunk rather than dropped
+        match chunks.last_mut() {
+            Some(last) if lines.len() - last_chunk_end_line_number <= min_lines_per_chunk => {
+                last.end = lines.len();
+            }
+            _ => chunks.push(Range {
+                start: last_chunk_end_line_number,
+                end: lines.len(),
+            }),
+        }
     }
     Ok(chunks
         .iter()
         .map(|chunk| {
             let start = chunk.start;
             let end = chunk.end;
-            Chunk {
+            CodeChunk {
                 line_range: start..end,
                 entities: vec![],
             }
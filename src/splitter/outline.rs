@@ -0,0 +1,499 @@
+//
+// outline.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    cache,
+    convert_captures,
+    parse_capture_for_entity,
+    token_splitter::Tokenizer,
+};
+use crate::{
+    lang::{
+        Lang,
+        LangConfig,
+        EMPTY_QUERY,
+    },
+    EntityType,
+    Result,
+    SplitError,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    collections::HashSet,
+    ops::Range,
+};
+use tree_sitter::{
+    Node,
+    Tree,
+};
+
+/// the indentation of a nested item in the rendered outline
+const INDENT: &str = "    ";
+
+/// The outline of a file: the signature and the doc comment of every entity, with the
+/// bodies elided and the entities nested under their parent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Outline {
+    /// the top level items of the file, in the order of the code
+    pub items: Vec<OutlineItem>,
+    /// the whole outline rendered as text
+    pub text: String,
+}
+
+/// An entity of an outline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutlineItem {
+    /// the name of the entity
+    pub name: String,
+    /// the type of the entity, `None` for a block holding methods without being an
+    /// entity itself, e.g. a Rust `impl` block or a Python class
+    pub entity_type: Option<EntityType>,
+    /// the lines of the entity including its doc comment, the end line is included
+    /// like in `Entity::completed_line_range`
+    pub line_range: Range<usize>,
+    /// the doc comment and the attributes above the entity
+    pub doc: Option<String>,
+    /// the signature of the entity with its body elided, e.g. `fn main() { ... }`
+    pub signature: String,
+    /// the number of references to the entity in the rest of the file, including the
+    /// references to its children
+    pub importance: usize,
+    /// the entities nested in the entity, e.g. the methods of a class
+    pub children: Vec<OutlineItem>,
+}
+
+impl Outline {
+    /// Renders the most important items of the outline within a token budget
+    ///
+    /// The items are picked by decreasing importance, a picked item keeps the
+    /// signature of its parent, then rendered in the order of the code. The budget is
+    /// counted item by item.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use devgen_splitter::{
+    ///     outline,
+    ///     ApproximateTokenizer,
+    /// };
+    ///
+    /// let code = "fn helper() {}\n\nfn main() {\n    helper();\n}\n";
+    /// let outline = outline("example.rs", code).unwrap();
+    /// assert_eq!(
+    ///     outline.render(5, &ApproximateTokenizer),
+    ///     "fn helper() { ... }"
+    /// );
+    /// ```
+    pub fn render(&self, max_tokens: usize, tokenizer: &dyn Tokenizer) -> String {
+        let mut flat = vec![];
+        flatten(&self.items, None, 0, &mut flat);
+        let mut order = (0..flat.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|index| std::cmp::Reverse(flat[*index].item.importance));
+        let mut kept = vec![false; flat.len()];
+        let mut tokens = 0;
+        for index in order {
+            // the item and its parents not kept yet
+            let mut added = vec![];
+            let mut next = Some(index);
+            while let Some(current) = next.filter(|current| !kept[*current]) {
+                added.push(current);
+                next = flat[current].parent;
+            }
+            let cost = added
+                .iter()
+                .map(|added| {
+                    let mut lines = vec![];
+                    render_item(
+                        flat[*added].item,
+                        flat[*added].depth,
+                        &|_| false,
+                        &mut lines,
+                    );
+                    tokenizer.count_tokens(&lines.join("\n"))
+                })
+                .sum::<usize>();
+            if tokens + cost <= max_tokens {
+                tokens += cost;
+                for added in added {
+                    kept[added] = true;
+                }
+            }
+        }
+        render_kept(&self.items, &flat, &kept)
+    }
+}
+
+/// An item of the outline with its parent, in the order of the code
+pub(crate) struct FlatItem<'a> {
+    pub(crate) item: &'a OutlineItem,
+    pub(crate) parent: Option<usize>,
+    pub(crate) depth: usize,
+}
+
+/// Returns the outline of a file, every entity captured by the query of its language
+/// with its signature and its doc comment
+///
+/// The methods are nested under their class, or under a block without entity type
+/// when the block itself isn't captured, e.g. a Rust `impl` block. The importance of
+/// an entity is the number of times its name appears in the rest of the file.
+///
+/// # Errors
+///
+/// Returns `SplitError::UnsupportedLanguage` if the language of the file is unknown.
+///
+/// # Example
+///
+/// ```
+/// use devgen_splitter::outline;
+///
+/// let code = "struct Point {\n    x: i32,\n}\n\nimpl Point {\n    /// the origin\n    fn origin() -> Self {\n        Point { x: 0 }\n    }\n}\n";
+/// let outline = outline("example.rs", code).unwrap();
+/// assert_eq!(
+///     outline.text,
+///     "struct Point { ... }\nimpl Point {\n    /// the origin\n    fn origin() -> Self { ... }\n}"
+/// );
+/// ```
+pub fn outline(filename: &str, code: &str) -> Result<Outline> {
+    let (lang_config, tree) = parse(filename, code)?;
+    outline_tree(lang_config, &tree, code)
+}
+
+/// Detects the language of the file and parses the code
+pub(crate) fn parse(filename: &str, code: &str) -> Result<(&'static LangConfig, Tree)> {
+    let Some(lang_config) = Lang::detect(filename, code) else {
+        return Err(SplitError::UnsupportedLanguage {
+            filename: filename.to_string(),
+        });
+    };
+    let tree = cache::with_parser(|parser| cache::parse_with(parser, lang_config, code, None))?;
+    Ok((lang_config, tree))
+}
+
+/// Returns the outline of the parsed code
+pub(crate) fn outline_tree(
+    lang_config: &'static LangConfig,
+    tree: &Tree,
+    code: &str,
+) -> Result<Outline> {
+    let entities = match lang_config.query == EMPTY_QUERY {
+        true => vec![],
+        false => {
+            let query = cache::global_query_cache().get(lang_config, lang_config.query)?;
+            convert_captures(&parse_capture_for_entity(&query, code, tree)?, code)
+        }
+    };
+    let lines = code.lines().collect::<Vec<&str>>();
+    let root = tree.root_node();
+    // the items with the byte range of their node
+    let mut nodes: Vec<(Range<usize>, OutlineItem)> = vec![];
+    for (entity, _) in &entities {
+        let Some(node) = root
+            .descendant_for_byte_range(entity.body_byte_range.start, entity.body_byte_range.end)
+        else {
+            continue;
+        };
+        if entity.entity_type == EntityType::Method {
+            if let Some(container) = container(&node) {
+                if !nodes
+                    .iter()
+                    .any(|(range, _)| *range == container.byte_range())
+                {
+                    let item = OutlineItem {
+                        name: entity.parent_name.clone().unwrap_or_default(),
+                        entity_type: None,
+                        line_range: container.start_position().row..container.end_position().row,
+                        doc: None,
+                        signature: signature(&container, code, &lines),
+                        importance: 0,
+                        children: vec![],
+                    };
+                    nodes.push((container.byte_range(), item));
+                }
+            }
+        }
+        let start_line = entity.start_line();
+        let doc = (start_line < entity.body_line_range.start).then(|| {
+            dedent(
+                &lines[start_line..entity.body_line_range.start.min(lines.len())],
+                indentation(lines[node.start_position().row]),
+            )
+        });
+        let item = OutlineItem {
+            name: entity.name.clone(),
+            entity_type: Some(entity.entity_type.clone()),
+            line_range: start_line..entity.body_line_range.end,
+            doc,
+            signature: signature(&node, code, &lines),
+            importance: 0,
+            children: vec![],
+        };
+        match nodes
+            .iter_mut()
+            .find(|(range, _)| *range == node.byte_range())
+        {
+            // the class of the methods is an entity
+            Some((_, container)) => {
+                container.name = item.name;
+                container.entity_type = item.entity_type;
+                container.line_range = item.line_range;
+                container.doc = item.doc;
+            }
+            None => nodes.push((node.byte_range(), item)),
+        }
+    }
+    nodes.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+    let mut items = nest(&mut nodes.into_iter().peekable(), usize::MAX);
+    for item in items.iter_mut() {
+        rank(item, &lines);
+    }
+    let text = render(&items, &|_| true);
+    Ok(Outline { items, text })
+}
+
+/// Nests the items sorted by their byte range under the items enclosing them
+fn nest(
+    nodes: &mut std::iter::Peekable<impl Iterator<Item = (Range<usize>, OutlineItem)>>,
+    end: usize,
+) -> Vec<OutlineItem> {
+    let mut items = vec![];
+    while let Some((range, mut item)) = nodes.next_if(|(range, _)| range.start < end) {
+        item.children = nest(nodes, range.end);
+        items.push(item);
+    }
+    items
+}
+
+/// Returns the node holding the method in its body, e.g. a class or an `impl` block
+fn container<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    let mut parent = node.parent();
+    while let Some(ancestor) = parent {
+        if ancestor
+            .child_by_field_name("body")
+            .is_some_and(|body| body.start_byte() <= node.start_byte())
+        {
+            return Some(ancestor);
+        }
+        parent = ancestor.parent();
+    }
+    None
+}
+
+/// Returns the text of the node with its body elided
+fn signature(node: &Node, code: &str, lines: &[&str]) -> String {
+    let line = lines.get(node.start_position().row).unwrap_or(&"");
+    // keep the keywords before the node on its line, e.g. `export` before a function
+    let prefix = line
+        .get(indentation(line)..node.start_position().column)
+        .filter(|prefix| !prefix.contains(['{', '}', ';', '=', '(']))
+        .unwrap_or_default();
+    let (text, elided) = match node.child_by_field_name("body") {
+        Some(body) => {
+            // the comments between the header and the body belong to the body
+            let mut cursor = node.walk();
+            let header_end = node
+                .children(&mut cursor)
+                .take_while(|child| child.id() != body.id())
+                .filter(|child| child.kind() != "comment")
+                .last()
+                .map_or(body.start_byte(), |child| child.end_byte());
+            let header = code[node.start_byte()..header_end].trim_end();
+            match code[body.byte_range()].starts_with('{') {
+                true => (header, " { ... }"),
+                false => (header, " ..."),
+            }
+        }
+        None => (&code[node.byte_range()], ""),
+    };
+    let text = format!("{}{}{}", prefix, text, elided);
+    dedent(&text.lines().collect::<Vec<&str>>(), indentation(line))
+}
+
+/// Sets the importance of the item and its children
+fn rank(item: &mut OutlineItem, lines: &[&str]) {
+    item.importance = match item.entity_type {
+        Some(_) => lines
+            .iter()
+            .enumerate()
+            .filter(|(line, _)| !(item.line_range.start..=item.line_range.end).contains(line))
+            .map(|(_, line)| count_word(line, &item.name))
+            .sum(),
+        None => 0,
+    };
+    for child in item.children.iter_mut() {
+        rank(child, lines);
+        item.importance += child.importance;
+    }
+}
+
+/// Returns the number of times the word appears in the text
+fn count_word(text: &str, word: &str) -> usize {
+    if word.is_empty() {
+        return 0;
+    }
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word)
+        .filter(|(start, _)| {
+            !text[..*start].chars().next_back().is_some_and(is_word)
+                && !text[start + word.len()..]
+                    .chars()
+                    .next()
+                    .is_some_and(is_word)
+        })
+        .count()
+}
+
+pub(crate) fn flatten<'a>(
+    items: &'a [OutlineItem],
+    parent: Option<usize>,
+    depth: usize,
+    flat: &mut Vec<FlatItem<'a>>,
+) {
+    for item in items {
+        flat.push(FlatItem {
+            item,
+            parent,
+            depth,
+        });
+        let index = flat.len() - 1;
+        flatten(&item.children, Some(index), depth + 1, flat);
+    }
+}
+
+/// Renders the items kept by their index in the flattened items
+pub(crate) fn render_kept(items: &[OutlineItem], flat: &[FlatItem], kept: &[bool]) -> String {
+    let kept = flat
+        .iter()
+        .zip(kept)
+        .filter(|(_, kept)| **kept)
+        .map(|(flat, _)| flat.item as *const OutlineItem)
+        .collect::<HashSet<*const OutlineItem>>();
+    render(items, &|item| kept.contains(&(item as *const OutlineItem)))
+}
+
+/// Renders the kept items, a kept item with kept children shows their signatures in
+/// place of its elided body
+pub(crate) fn render(items: &[OutlineItem], keep: &dyn Fn(&OutlineItem) -> bool) -> String {
+    let mut lines = vec![];
+    for item in items.iter().filter(|item| keep(item)) {
+        render_item(item, 0, keep, &mut lines);
+    }
+    lines.join("\n")
+}
+
+pub(crate) fn render_item(
+    item: &OutlineItem,
+    depth: usize,
+    keep: &dyn Fn(&OutlineItem) -> bool,
+    lines: &mut Vec<String>,
+) {
+    let indent = INDENT.repeat(depth);
+    let mut push = |text: &str| {
+        for line in text.lines() {
+            lines.push(format!("{}{}", indent, line).trim_end().to_string());
+        }
+    };
+    if let Some(doc) = &item.doc {
+        push(doc);
+    }
+    let children = item
+        .children
+        .iter()
+        .filter(|child| keep(child))
+        .collect::<Vec<&OutlineItem>>();
+    if children.is_empty() {
+        push(&item.signature);
+        return;
+    }
+    let (header, close) = match item.signature.strip_suffix(" { ... }") {
+        Some(header) => (format!("{} {{", header), Some("}")),
+        None => (
+            item.signature
+                .strip_suffix(" ...")
+                .unwrap_or(&item.signature)
+                .to_string(),
+            None,
+        ),
+    };
+    push(&header);
+    for child in children {
+        render_item(child, depth + 1, keep, lines);
+    }
+    if let Some(close) = close {
+        lines.push(format!("{}{}", INDENT.repeat(depth), close));
+    }
+}
+
+/// Returns the identifiers of the code with their line, e.g. the names of the
+/// functions called and of the types used
+pub(crate) fn identifiers(tree: &Tree, code: &str) -> Vec<(String, usize)> {
+    let mut identifiers = vec![];
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        if node.child_count() == 0 && node.is_named() && node.kind().ends_with("identifier") {
+            if let Ok(text) = node.utf8_text(code.as_bytes()) {
+                identifiers.push((text.to_string(), node.start_position().row));
+            }
+        }
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                return identifiers;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
+
+/// Returns the number of bytes of the leading spaces and tabs of the line, the other
+/// whitespaces may be several bytes long and are kept
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches([' ', '\t']).len()
+}
+
+/// Joins the lines, removing at most `indent` leading spaces and tabs from each line
+fn dedent(lines: &[&str], indent: usize) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            let strip = indentation(line).min(indent);
+            line[strip..].trim_end()
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("helper();", "helper", 1)]
+    #[case("helper_two(); helper(helper)", "helper", 2)]
+    #[case("self.helper", "helper", 1)]
+    #[case("helpers", "helper", 0)]
+    #[case("anything", "", 0)]
+    fn test_count_word(#[case] text: &str, #[case] word: &str, #[case] expected: usize) {
+        assert_eq!(count_word(text, word), expected);
+    }
+
+    #[rstest]
+    #[case(vec!["    /// doc", "    #[test]"], 4, "/// doc\n#[test]")]
+    #[case(vec!["fn a(", "        x: i32,", "    )"], 4, "fn a(\n    x: i32,\n)")]
+    #[case(vec!["  a", "b"], 4, "a\nb")]
+    #[case(vec!["  a", "\u{3000}b", " \u{3000}c"], 2, "a\n\u{3000}b\n\u{3000}c")]
+    fn test_dedent(#[case] lines: Vec<&str>, #[case] indent: usize, #[case] expected: &str) {
+        assert_eq!(dedent(&lines, indent), expected);
+    }
+}
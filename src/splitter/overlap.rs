@@ -0,0 +1,152 @@
+//
+// overlap.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    CodeChunk,
+    CodeEntity,
+};
+use crate::ChunkOverlap;
+use std::{
+    collections::BTreeSet,
+    ops::Range,
+};
+use tree_sitter::{
+    Node,
+    Tree,
+};
+
+/// The lines where a chunk is allowed to start or end when it is extended by the overlap
+pub(crate) struct SyntaxBoundaries {
+    /// a chunk can start at these lines
+    starts: BTreeSet<usize>,
+    /// a chunk can end (exclusive) at these lines
+    ends: BTreeSet<usize>,
+    /// the total line count of the source code
+    line_count: usize,
+}
+
+impl SyntaxBoundaries {
+    /// Collects the syntactic boundaries of the tree
+    ///
+    /// Every named node that covers whole lines, e.g. a statement, a field or a
+    /// comment, contributes its first line as a start boundary and the line after
+    /// its last line as an end boundary. Blank lines are boundaries as well.
+    pub(crate) fn new(tree: &Tree, lines: &[&str]) -> Self {
+        let mut starts = BTreeSet::new();
+        let mut ends = BTreeSet::new();
+        for (i, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                starts.insert(i + 1);
+                ends.insert(i);
+            }
+        }
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            if node.is_named() {
+                if let Some(line_range) = Self::covered_lines(&node, lines) {
+                    starts.insert(line_range.start);
+                    ends.insert(line_range.end);
+                }
+            }
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return Self {
+                        starts,
+                        ends,
+                        line_count: lines.len(),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Returns the lines covered by the node if it starts at the beginning of a line
+    /// and finishes at the end of a line
+    ///
+    /// A trailing `,` or `;` is allowed after a node with children, so a field or
+    /// an argument on its own line counts, while a bare identifier in a list doesn't.
+    fn covered_lines(node: &Node, lines: &[&str]) -> Option<Range<usize>> {
+        let start = node.start_position();
+        let line = lines.get(start.row)?;
+        if start.column > line.len() - line.trim_start().len() {
+            return None;
+        }
+        let end = node.end_position();
+        // some grammars include the trailing newline in the node
+        if end.column == 0 && end.row > start.row {
+            return Some(start.row..end.row);
+        }
+        let line = lines.get(end.row)?.trim_end();
+        let line = if node.child_count() > 0 {
+            line.trim_end_matches([',', ';'])
+        } else {
+            line
+        };
+        if end.column >= line.len() {
+            Some(start.row..end.row + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Extends the line range by the overlap, snapping both edges to syntactic boundaries
+    pub(crate) fn extend(&self, range: &Range<usize>, overlap: &ChunkOverlap) -> Range<usize> {
+        let lines = match overlap {
+            ChunkOverlap::Lines(lines) => *lines,
+            ChunkOverlap::Ratio(ratio) => (range.len() as f32 * ratio.max(0.0)).ceil() as usize,
+        };
+        if lines == 0 {
+            return range.clone();
+        }
+        let start = self
+            .starts
+            .range(range.start.saturating_sub(lines)..range.start)
+            .next()
+            .copied()
+            .unwrap_or(range.start);
+        let end_limit = (range.end + lines).min(self.line_count);
+        let end = if range.end < end_limit {
+            self.ends
+                .range(range.end + 1..=end_limit)
+                .next_back()
+                .copied()
+                .unwrap_or(range.end)
+        } else {
+            range.end
+        };
+        start..end
+    }
+}
+
+/// Extends the code chunks by the overlap and reports the entities in the overlap region
+pub(crate) fn extend_code_chunks(
+    chunks: &mut [CodeChunk],
+    entities: &[(CodeEntity, Vec<Node>)],
+    boundaries: &SyntaxBoundaries,
+    overlap: &ChunkOverlap,
+) {
+    for chunk in chunks.iter_mut() {
+        let line_range = boundaries.extend(&chunk.line_range, overlap);
+        for (entity, _) in entities {
+            if entity.start_line() >= line_range.end
+                || entity.body_line_range.end < line_range.start
+            {
+                continue;
+            }
+            if !chunk.entities.contains(entity) {
+                chunk.entities.push(entity.clone());
+            }
+        }
+        chunk
+            .entities
+            .sort_by_key(|entity| entity.body_line_range.start);
+        chunk.line_range = line_range;
+    }
+}
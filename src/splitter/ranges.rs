@@ -0,0 +1,93 @@
+//
+// ranges.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use crate::Chunk;
+use std::ops::Range;
+
+/// Returns true if the end-exclusive ranges overlap, an empty range overlaps a range
+/// it is strictly inside of
+pub(crate) fn intersects(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+/// Moves the range by the given number of lines
+pub(crate) fn shift_range(range: &Range<usize>, delta: isize) -> Range<usize> {
+    range.start.saturating_add_signed(delta)..range.end.saturating_add_signed(delta)
+}
+
+/// Moves the chunk, its entities, its references and its imports by the given number of
+/// lines
+pub(crate) fn shift_chunk(mut chunk: Chunk, delta: isize) -> Chunk {
+    chunk.line_range = shift_range(&chunk.line_range, delta);
+    for entity in chunk.entities.iter_mut() {
+        entity.completed_line_range = shift_range(&entity.completed_line_range, delta);
+        entity.chunk_line_range = shift_range(&entity.chunk_line_range, delta);
+        entity.parent_line_range = entity
+            .parent_line_range
+            .as_ref()
+            .map(|range| shift_range(range, delta));
+    }
+    for reference in chunk.references.iter_mut() {
+        reference.line = reference.line.saturating_add_signed(delta);
+    }
+    for import in chunk.imports.iter_mut() {
+        import.line_range = shift_range(&import.line_range, delta);
+    }
+    chunk
+}
+
+/// Returns the byte offset of the start of every line
+pub(crate) fn line_starts(code: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(code.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Import,
+        Reference,
+        ReferenceKind,
+    };
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0..2, 2..4, false)]
+    #[case(0..3, 2..4, true)]
+    // an empty range strictly inside the other one marks a position in it
+    #[case(1..1, 0..4, true)]
+    #[case(5..6, 3..3, false)]
+    fn test_intersects(#[case] a: Range<usize>, #[case] b: Range<usize>, #[case] intersect: bool) {
+        assert_eq!(intersects(&a, &b), intersect);
+    }
+
+    #[test]
+    fn test_shift_chunk() {
+        let mut chunk = Chunk::new(4..8, vec![]);
+        chunk.references.push(Reference {
+            name: "load".to_string(),
+            kind: ReferenceKind::Call,
+            line: 5,
+        });
+        chunk.imports.push(Import {
+            statement: "use std::fs::read;".to_string(),
+            names: vec!["read".to_string()],
+            line_range: 0..0,
+        });
+        let chunk = shift_chunk(chunk, 2);
+        assert_eq!(chunk.line_range, 6..10);
+        assert_eq!(chunk.references[0].line, 7);
+        assert_eq!(chunk.imports[0].line_range, 2..2);
+    }
+
+    #[test]
+    fn test_line_starts() {
+        assert_eq!(line_starts(""), vec![0]);
+        assert_eq!(line_starts("a\nbc\n"), vec![0, 2, 5]);
+    }
+}
@@ -0,0 +1,238 @@
+//
+// references.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::cache;
+use crate::{
+    lang::{
+        LangConfig,
+        EMPTY_QUERY,
+    },
+    Chunk,
+    Reference,
+    ReferenceKind,
+    Result,
+};
+use std::collections::HashSet;
+use tree_sitter::{
+    Node,
+    QueryCursor,
+    Tree,
+};
+
+/// the suffixes of the node kinds defining a named entity, e.g. `struct_item`
+const DEFINITION_SUFFIXES: &[&str] = &["_item", "_definition", "_declaration", "_spec"];
+
+/// Collects the references of the code with the reference query of the language and
+/// adds them to the chunks using them
+///
+/// A name is listed once per chunk and kind, with the line of its first use in the
+/// chunk. The name of a definition, e.g. the name of a struct, isn't a reference.
+pub(crate) fn collect_references(
+    lang_config: &'static LangConfig,
+    tree: &Tree,
+    code: &str,
+    chunks: &mut [Chunk],
+) -> Result<()> {
+    if lang_config.reference_query == EMPTY_QUERY {
+        return Ok(());
+    }
+    let query = cache::global_query_cache().get(lang_config, lang_config.reference_query)?;
+    let mut references = vec![];
+    let mut query_cursor = QueryCursor::new();
+    for m in query_cursor.matches(&query, tree.root_node(), code.as_bytes()) {
+        for c in m.captures {
+            let kind = match query.capture_names()[c.index as usize] {
+                "reference.call" => ReferenceKind::Call,
+                "reference.type" => ReferenceKind::Type,
+                "reference.module" => ReferenceKind::Module,
+                _ => continue,
+            };
+            if is_definition_name(&c.node) {
+                continue;
+            }
+            if kind == ReferenceKind::Module {
+                let mut paths = vec![];
+                module_paths(c.node, code, "", &mut paths);
+                references.extend(paths.into_iter().map(|(node, name)| {
+                    (
+                        node.start_byte(),
+                        Reference {
+                            name,
+                            kind,
+                            line: node.start_position().row,
+                        },
+                    )
+                }));
+                continue;
+            }
+            let Ok(text) = c.node.utf8_text(code.as_bytes()) else {
+                continue;
+            };
+            references.push((
+                c.node.start_byte(),
+                Reference {
+                    name: text.to_string(),
+                    kind,
+                    line: c.node.start_position().row,
+                },
+            ));
+        }
+    }
+    references.sort_by_key(|(start_byte, _)| *start_byte);
+    for chunk in chunks.iter_mut() {
+        let mut seen = HashSet::new();
+        chunk.references = references
+            .iter()
+            .filter(|(_, reference)| chunk.line_range.contains(&reference.line))
+            .filter(|(_, reference)| seen.insert((reference.name.clone(), reference.kind)))
+            .map(|(_, reference)| reference.clone())
+            .collect();
+    }
+    Ok(())
+}
+
+/// Collects the paths of the module node with their node, one path for every leaf of
+/// a Rust use tree, e.g. `std::collections::{HashMap, HashSet}` imports
+/// `std::collections::HashMap` and `std::collections::HashSet`
+fn module_paths<'a>(node: Node<'a>, code: &str, prefix: &str, paths: &mut Vec<(Node<'a>, String)>) {
+    let join = |path: &str| match (prefix, path) {
+        ("", path) => path.to_string(),
+        // `self` in a list imports the module of the list
+        (prefix, "self") => prefix.to_string(),
+        (prefix, path) => format!("{}::{}", prefix, path),
+    };
+    match node.kind() {
+        "scoped_use_list" => {
+            let prefix = match node
+                .child_by_field_name("path")
+                .and_then(|path| path.utf8_text(code.as_bytes()).ok())
+            {
+                Some(path) => join(path),
+                None => prefix.to_string(),
+            };
+            if let Some(list) = node.child_by_field_name("list") {
+                module_paths(list, code, &prefix, paths);
+            }
+        }
+        "use_list" => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                if !child.is_extra() {
+                    module_paths(child, code, prefix, paths);
+                }
+            }
+        }
+        // the alias isn't part of the path
+        "use_as_clause" => {
+            if let Some(path) = node.child_by_field_name("path") {
+                module_paths(path, code, prefix, paths);
+            }
+        }
+        _ => {
+            let Ok(text) = node.utf8_text(code.as_bytes()) else {
+                return;
+            };
+            let path = text
+                .trim_matches(|c| c == '"' || c == '\'' || c == '`')
+                .split_whitespace()
+                .collect::<String>();
+            paths.push((node, join(&path)));
+        }
+    }
+}
+
+/// Returns true if the node is the name of the entity defined by its parent
+fn is_definition_name(node: &Node) -> bool {
+    node.parent().is_some_and(|parent| {
+        DEFINITION_SUFFIXES
+            .iter()
+            .any(|suffix| parent.kind().ends_with(suffix))
+            && parent.child_by_field_name("name") == Some(*node)
+    })
+}
+
+#[cfg(all(test, feature = "lang-rust"))]
+mod tests {
+    use super::*;
+    use crate::{
+        split,
+        SplitOptions,
+    };
+
+    fn names(chunk: &Chunk, kind: ReferenceKind) -> Vec<&str> {
+        chunk
+            .references
+            .iter()
+            .filter(|reference| reference.kind == kind)
+            .map(|reference| reference.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_collect_rust_references() {
+        let code = r#"use std::collections::{
+    HashMap,
+    HashSet,
+};
+use std::io::{self, Read as _};
+
+struct Point {
+    x: i32,
+}
+
+fn build(items: Vec<Point>) -> HashMap<String, Point> {
+    let mut map = HashMap::new();
+    for item in items {
+        map.insert(item.x.to_string(), item);
+        // the tokens of a macro aren't parsed
+        println!("{}", std::mem::size_of::<Point>());
+        let size = std::mem::size_of::<Point>();
+    }
+    map
+}
+"#;
+        let options = SplitOptions {
+            chunk_line_limit: 100,
+            collect_references: true,
+            ..Default::default()
+        };
+        let chunks = split("test.rs", code, &options).unwrap();
+        let chunk = &chunks[0];
+        assert_eq!(
+            names(chunk, ReferenceKind::Module),
+            vec![
+                "std::collections::HashMap",
+                "std::collections::HashSet",
+                "std::io",
+                "std::io::Read"
+            ]
+        );
+        assert!(chunk
+            .references
+            .iter()
+            .any(|reference| reference.name == "std::collections::HashSet" && reference.line == 2));
+        // the struct name is a definition, the type is used from line 10 on
+        assert_eq!(
+            names(chunk, ReferenceKind::Type),
+            vec!["Vec", "Point", "HashMap", "String"]
+        );
+        assert!(chunk
+            .references
+            .iter()
+            .any(|reference| reference.name == "Point" && reference.line == 10));
+        assert_eq!(
+            names(chunk, ReferenceKind::Call),
+            vec!["new", "insert", "to_string", "println", "size_of"]
+        );
+        // the references are only collected on demand
+        let options = SplitOptions {
+            collect_references: false,
+            ..options
+        };
+        let chunks = split("test.rs", code, &options).unwrap();
+        assert!(chunks[0].references.is_empty());
+    }
+}
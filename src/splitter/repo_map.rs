@@ -0,0 +1,328 @@
+//
+// repo_map.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use super::{
+    outline::{
+        self,
+        FlatItem,
+        Outline,
+    },
+    token_splitter::Tokenizer,
+};
+use crate::{
+    EntityType,
+    Result,
+};
+use serde::Serialize;
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    ops::Range,
+};
+
+/// the probability of following a reference rather than jumping to a random entity
+const DAMPING: f64 = 0.85;
+/// the ranks stop changing once their total change is under this threshold
+const TOLERANCE: f64 = 1e-9;
+const MAX_ITERATIONS: usize = 100;
+
+/// A map of a repository: the outline of every file and its entities ranked by how
+/// much the other files depend on them
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoMap {
+    /// the entities of every file, the most central first
+    pub entities: Vec<RankedEntity>,
+    /// the outline of every file with its path, in the order of the paths
+    pub outlines: Vec<(String, Outline)>,
+}
+
+/// An entity of a repository map with its rank
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankedEntity {
+    /// the path of the file defining the entity
+    pub path: String,
+    /// the name of the entity
+    pub name: String,
+    /// the type of the entity, `None` for a block holding methods, see `OutlineItem`
+    pub entity_type: Option<EntityType>,
+    /// the lines of the entity, the end line is included
+    pub line_range: Range<usize>,
+    /// the signature of the entity with its body elided
+    pub signature: String,
+    /// the PageRank of the entity in the graph of the references between the files,
+    /// the ranks of all the entities and files sum to one
+    pub rank: f64,
+    /// the index of the outline and of the item in the flattened outline
+    #[serde(skip)]
+    location: (usize, usize),
+}
+
+/// A node of the reference graph, an entity or the top level code of a file
+enum GraphNode {
+    Entity { file: usize, item: usize },
+    File,
+}
+
+impl RepoMap {
+    /// Renders the signatures of the `top_n` most central entities within a token
+    /// budget, grouped by file
+    ///
+    /// Every entity keeps the signature of its parent, the budget is counted entity by
+    /// entity like in `Outline::render`.
+    pub fn render(&self, top_n: usize, max_tokens: usize, tokenizer: &dyn Tokenizer) -> String {
+        let flats = self
+            .outlines
+            .iter()
+            .map(|(_, outline)| {
+                let mut flat = vec![];
+                outline::flatten(&outline.items, None, 0, &mut flat);
+                flat
+            })
+            .collect::<Vec<Vec<FlatItem>>>();
+        let mut kept = flats
+            .iter()
+            .map(|flat| vec![false; flat.len()])
+            .collect::<Vec<Vec<bool>>>();
+        let mut tokens = 0;
+        for entity in self.entities.iter().take(top_n) {
+            let (file, index) = entity.location;
+            let mut cost = match kept[file].contains(&true) {
+                true => 0,
+                false => tokenizer.count_tokens(&header(&self.outlines[file].0)),
+            };
+            let mut added = vec![];
+            let mut next = Some(index);
+            while let Some(current) = next.filter(|current| !kept[file][*current]) {
+                let mut lines = vec![];
+                let flat = &flats[file][current];
+                outline::render_item(flat.item, flat.depth, &|_| false, &mut lines);
+                cost += tokenizer.count_tokens(&lines.join("\n"));
+                added.push(current);
+                next = flat.parent;
+            }
+            if tokens + cost <= max_tokens {
+                tokens += cost;
+                for added in added {
+                    kept[file][added] = true;
+                }
+            }
+        }
+        let mut sections = vec![];
+        for (file, (path, outline)) in self.outlines.iter().enumerate() {
+            if !kept[file].contains(&true) {
+                continue;
+            }
+            let text = outline::render_kept(&outline.items, &flats[file], &kept[file]);
+            sections.push(format!("{}\n{}", header(path), text));
+        }
+        sections.join("\n\n")
+    }
+}
+
+fn header(path: &str) -> String {
+    format!("{}:", path)
+}
+
+/// Builds the map of a repository from its files, given by their path and their code
+///
+/// A reference is an identifier used in one file and defined by an entity of another
+/// file, a file defining the identifier itself is assumed to use its own definition.
+/// The entities are ranked by PageRank over the references, a reference outside any
+/// entity comes from the file. A reference to a name defined in several files is
+/// shared between them. The files of an unsupported language are skipped.
+///
+/// # Example
+///
+/// ```
+/// use devgen_splitter::{
+///     repo_map,
+///     ApproximateTokenizer,
+/// };
+///
+/// let files = vec![
+///     (
+///         "src/point.rs",
+///         "pub struct Point {\n    x: i32,\n}\n\npub fn unused() {}\n",
+///     ),
+///     (
+///         "src/main.rs",
+///         "fn main() {\n    let p = Point { x: 1 };\n}\n",
+///     ),
+/// ];
+/// let map = repo_map(files);
+/// assert_eq!(map.entities[0].name, "Point");
+/// assert_eq!(
+///     map.render(1, 100, &ApproximateTokenizer),
+///     "src/point.rs:\npub struct Point { ... }"
+/// );
+/// ```
+pub fn repo_map<P: AsRef<str>, C: AsRef<str>>(files: impl IntoIterator<Item = (P, C)>) -> RepoMap {
+    let files = files
+        .into_iter()
+        .filter_map(|(path, code)| map_file(path.as_ref(), code.as_ref()).ok())
+        .collect::<Vec<MappedFile>>();
+    build_repo_map(files)
+}
+
+/// A file of a repository map: its path, its outline and its identifiers with their
+/// line
+pub(crate) type MappedFile = (String, Outline, Vec<(String, usize)>);
+
+/// Parses the file and returns its outline and its identifiers
+pub(crate) fn map_file(path: &str, code: &str) -> Result<MappedFile> {
+    let (lang_config, tree) = outline::parse(path, code)?;
+    let outline = outline::outline_tree(lang_config, &tree, code)?;
+    Ok((path.to_string(), outline, outline::identifiers(&tree, code)))
+}
+
+/// Builds the map of the parsed files
+pub(crate) fn build_repo_map(mut files: Vec<MappedFile>) -> RepoMap {
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let flats = files
+        .iter()
+        .map(|(_, outline, _)| {
+            let mut flat = vec![];
+            outline::flatten(&outline.items, None, 0, &mut flat);
+            flat
+        })
+        .collect::<Vec<Vec<FlatItem>>>();
+    let mut nodes = vec![];
+    // the entity nodes of every file, in the order of the flattened outline
+    let mut file_entities: Vec<Vec<(usize, Range<usize>)>> = vec![];
+    let mut definitions: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    // the names defined by every file
+    let mut file_definitions: Vec<HashSet<&str>> = vec![];
+    for (file, flat) in flats.iter().enumerate() {
+        let mut entities = vec![];
+        let mut defined = HashSet::new();
+        for (item, flat) in flat.iter().enumerate() {
+            let node = nodes.len();
+            nodes.push(GraphNode::Entity { file, item });
+            entities.push((node, flat.item.line_range.clone()));
+            if flat.item.name.is_empty() {
+                continue;
+            }
+            definitions
+                .entry(flat.item.name.as_str())
+                .or_default()
+                .push((file, node));
+            defined.insert(flat.item.name.as_str());
+        }
+        file_entities.push(entities);
+        file_definitions.push(defined);
+    }
+    let mut edges: HashMap<(usize, usize), f64> = HashMap::new();
+    for (file, (_, _, identifiers)) in files.iter().enumerate() {
+        let file_node = nodes.len();
+        nodes.push(GraphNode::File);
+        let defined = &file_definitions[file];
+        for (name, line) in identifiers {
+            if defined.contains(name.as_str()) {
+                continue;
+            }
+            let Some(targets) = definitions.get(name.as_str()) else {
+                continue;
+            };
+            // the innermost entity using the name
+            let source = file_entities[file]
+                .iter()
+                .filter(|(_, line_range)| line_range.start <= *line && *line <= line_range.end)
+                .max_by_key(|(_, line_range)| line_range.start)
+                .map_or(file_node, |(node, _)| *node);
+            for (_, target) in targets {
+                *edges.entry((source, *target)).or_default() += 1.0 / targets.len() as f64;
+            }
+        }
+    }
+    let ranks = page_rank(nodes.len(), &edges);
+    let mut entities = nodes
+        .iter()
+        .zip(&ranks)
+        .filter_map(|(node, rank)| match node {
+            GraphNode::Entity { file, item: index } => {
+                let item = flats[*file][*index].item;
+                Some(RankedEntity {
+                    path: files[*file].0.clone(),
+                    name: item.name.clone(),
+                    entity_type: item.entity_type.clone(),
+                    line_range: item.line_range.clone(),
+                    signature: item.signature.clone(),
+                    rank: *rank,
+                    location: (*file, *index),
+                })
+            }
+            GraphNode::File => None,
+        })
+        .collect::<Vec<RankedEntity>>();
+    // the order of the code breaks the ties
+    entities.sort_by(|a, b| b.rank.total_cmp(&a.rank).then(a.location.cmp(&b.location)));
+    RepoMap {
+        entities,
+        outlines: files
+            .into_iter()
+            .map(|(path, outline, _)| (path, outline))
+            .collect(),
+    }
+}
+
+/// Returns the PageRank of every node of the weighted graph, the rank of a node without
+/// outgoing edges is spread over all the nodes
+fn page_rank(count: usize, edges: &HashMap<(usize, usize), f64>) -> Vec<f64> {
+    if count == 0 {
+        return vec![];
+    }
+    let mut out_weights = vec![0.0; count];
+    for ((source, _), weight) in edges {
+        out_weights[*source] += weight;
+    }
+    let mut edges = edges
+        .iter()
+        .map(|((source, target), weight)| (*source, *target, *weight))
+        .collect::<Vec<(usize, usize, f64)>>();
+    // a fixed order keeps the sums, and the ranks, deterministic
+    edges.sort_by_key(|(source, target, _)| (*source, *target));
+    let mut ranks = vec![1.0 / count as f64; count];
+    for _ in 0..MAX_ITERATIONS {
+        let dangling = (0..count)
+            .filter(|node| out_weights[*node] == 0.0)
+            .map(|node| ranks[node])
+            .sum::<f64>();
+        let base = (1.0 - DAMPING + DAMPING * dangling) / count as f64;
+        let mut next = vec![base; count];
+        for (source, target, weight) in &edges {
+            next[*target] += DAMPING * ranks[*source] * weight / out_weights[*source];
+        }
+        let change = next
+            .iter()
+            .zip(&ranks)
+            .map(|(next, rank)| (next - rank).abs())
+            .sum::<f64>();
+        ranks = next;
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_rank() {
+        // 0 -> 2, 1 -> 2, 2 -> 0
+        let edges = HashMap::from([((0, 2), 1.0), ((1, 2), 1.0), ((2, 0), 1.0)]);
+        let ranks = page_rank(3, &edges);
+        assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(ranks[2] > ranks[0] && ranks[0] > ranks[1]);
+        assert!(page_rank(0, &edges).is_empty());
+        // without edges every node has the same rank
+        assert_eq!(page_rank(2, &HashMap::new()), vec![0.5, 0.5]);
+    }
+}
@@ -0,0 +1,85 @@
+//
+// token_splitter.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use crate::Chunk;
+use std::ops::Range;
+
+/// Counts the tokens of a text, used to keep every chunk under a token budget
+pub trait Tokenizer: Send + Sync {
+    /// Returns the number of tokens in the text
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// Estimates the token count as one token for every four characters
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApproximateTokenizer;
+
+impl Tokenizer for ApproximateTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+}
+
+/// Splits every chunk with more tokens than `max_tokens` into chunks of whole lines
+///
+/// A single line with more tokens than the limit becomes a chunk of its own.
+pub(crate) fn limit_tokens(
+    chunks: Vec<Chunk>,
+    lines: &[&str],
+    tokenizer: &dyn Tokenizer,
+    max_tokens: usize,
+) -> Vec<Chunk> {
+    let mut limited = vec![];
+    for chunk in chunks {
+        let end = chunk.line_range.end.min(lines.len());
+        let start = chunk.line_range.start.min(end);
+        let text = lines[start..end].join("\n");
+        if tokenizer.count_tokens(&text) <= max_tokens {
+            limited.push(chunk);
+            continue;
+        }
+        let mut sub_start = start;
+        let mut sub_tokens = 0;
+        for (line_number, line) in lines.iter().enumerate().take(end).skip(start) {
+            let line_tokens = tokenizer.count_tokens(line);
+            if line_number > sub_start && sub_tokens + line_tokens > max_tokens {
+                limited.push(sub_chunk(&chunk, sub_start..line_number));
+                sub_start = line_number;
+                sub_tokens = 0;
+            }
+            sub_tokens += line_tokens;
+        }
+        if sub_start < end {
+            limited.push(sub_chunk(&chunk, sub_start..end));
+        }
+    }
+    limited
+}
+
+/// Creates a chunk covering a part of the given chunk with the entities in that part
+fn sub_chunk(chunk: &Chunk, line_range: Range<usize>) -> Chunk {
+    let entities = chunk
+        .entities
+        .iter()
+        .filter(|entity| {
+            entity.completed_line_range.start < line_range.end
+                && entity.completed_line_range.end >= line_range.start
+        })
+        .map(|entity| {
+            let mut entity = entity.clone();
+            entity.chunk_line_range = line_range.start.max(entity.completed_line_range.start)
+                ..line_range.end.min(entity.completed_line_range.end);
+            entity
+        })
+        .collect();
+    Chunk {
+        line_range,
+        entities,
+        id: String::new(),
+        content_hash: String::new(),
+        ..chunk.clone()
+    }
+}
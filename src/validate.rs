@@ -0,0 +1,220 @@
+//
+// validate.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use crate::Chunk;
+#[cfg(feature = "notebook")]
+use crate::Notebook;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::ops::Range;
+
+/// A problem found in a list of chunks by `validate_chunks`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChunkIssue {
+    /// the lines are not covered by any chunk
+    Gap {
+        /// the uncovered lines
+        line_range: Range<usize>,
+    },
+    /// the lines are covered by two chunks
+    Overlap {
+        /// the indexes of the two chunks, the first one is the chunk reaching the
+        /// furthest line among the previous chunks
+        chunks: (usize, usize),
+        /// the lines covered by both chunks
+        line_range: Range<usize>,
+    },
+    /// the chunk ends after the last line of the file or starts after it ends
+    OutOfBounds {
+        /// the index of the chunk
+        chunk: usize,
+        /// the line range of the chunk
+        line_range: Range<usize>,
+    },
+    /// the chunk doesn't contain any line
+    Empty {
+        /// the index of the chunk
+        chunk: usize,
+    },
+}
+
+/// A problem found in the chunks of a cell of a notebook by `validate_notebook_chunks`
+#[cfg(feature = "notebook")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CellIssue {
+    /// the index of the cell in the notebook
+    pub cell: usize,
+    /// the issue is in the outputs of the cell rather than in its source
+    pub output: bool,
+    /// the issue, its line ranges are relative to the source or the outputs of the cell
+    pub issue: ChunkIssue,
+}
+
+/// Checks that the chunks tile the whole code, reporting every gap, overlap,
+/// out-of-bounds range and empty chunk
+///
+/// The chunks are expected in the order returned by `split`. Chunks split with an
+/// overlap are reported as overlapping by design. The chunks of a Jupyter notebook are
+/// checked with `validate_notebook_chunks`.
+///
+/// # Arguments
+///
+/// * `code` - The source code the chunks were split from.
+/// * `chunks` - The chunks to check.
+///
+/// # Returns
+///
+/// The issues found, an empty vector means the chunks tile the code.
+///
+/// # Example
+///
+/// ```
+/// use devgen_splitter::{
+///     split,
+///     validate_chunks,
+///     SplitOptions,
+/// };
+///
+/// let code = "fn main() {\n    println!(\"Hello, world!\");\n}\n";
+/// let options = SplitOptions {
+///     chunk_line_limit: 5,
+///     ..Default::default()
+/// };
+/// let chunks = split("example.rs", code, &options).unwrap();
+/// assert!(validate_chunks(code, &chunks).is_empty());
+/// ```
+pub fn validate_chunks(code: &str, chunks: &[Chunk]) -> Vec<ChunkIssue> {
+    let mut issues = vec![];
+    validate_ranges(
+        code.lines().count(),
+        chunks.iter().map(|chunk| &chunk.line_range).enumerate(),
+        &mut issues,
+    );
+    issues
+}
+
+/// Checks that the chunks of every cell of a notebook tile the source of the cell, and
+/// the outputs of the cell when they are split
+///
+/// The chunks are checked with `NotebookCell::line_range`, the line ranges of the issues
+/// are relative to the source or the outputs of their cell.
+///
+/// # Arguments
+///
+/// * `notebook` - The notebook the chunks were split from.
+/// * `chunks` - The chunks to check.
+///
+/// # Returns
+///
+/// The issues found with their cell, an empty vector means the chunks tile the cells.
+#[cfg(feature = "notebook")]
+pub fn validate_notebook_chunks(notebook: &Notebook, chunks: &[Chunk]) -> Vec<CellIssue> {
+    let mut issues = vec![];
+    for (index, cell) in notebook.cells.iter().enumerate() {
+        let line_counts = [
+            (false, cell.source.lines().count()),
+            (true, cell.outputs.lines().count()),
+        ];
+        for (output, line_count) in line_counts {
+            let cell_chunks = chunks
+                .iter()
+                .enumerate()
+                .filter_map(|(i, chunk)| {
+                    let cell = chunk.cell.as_ref()?;
+                    (cell.index == index && cell.output == output).then_some((i, &cell.line_range))
+                })
+                .collect::<Vec<_>>();
+            if output && cell_chunks.is_empty() {
+                continue;
+            }
+            let mut cell_issues = vec![];
+            validate_ranges(line_count, cell_chunks.into_iter(), &mut cell_issues);
+            issues.extend(cell_issues.into_iter().map(|issue| CellIssue {
+                cell: index,
+                output,
+                issue,
+            }));
+        }
+    }
+    issues
+}
+
+/// Checks that the line ranges, with the index of their chunk, tile the lines
+fn validate_ranges<'a>(
+    line_count: usize,
+    line_ranges: impl Iterator<Item = (usize, &'a Range<usize>)>,
+    issues: &mut Vec<ChunkIssue>,
+) {
+    let mut covered = 0;
+    // the index of the chunk that reached the covered line
+    let mut furthest = None;
+    for (i, line_range) in line_ranges {
+        if line_range.is_empty() {
+            issues.push(ChunkIssue::Empty { chunk: i });
+        }
+        if line_range.end > line_count || line_range.start > line_range.end {
+            issues.push(ChunkIssue::OutOfBounds {
+                chunk: i,
+                line_range: line_range.clone(),
+            });
+        }
+        if line_range.start > covered {
+            issues.push(ChunkIssue::Gap {
+                line_range: covered..line_range.start,
+            });
+        } else if let Some(furthest) = furthest {
+            if line_range.start < covered && !line_range.is_empty() {
+                issues.push(ChunkIssue::Overlap {
+                    chunks: (furthest, i),
+                    line_range: line_range.start..covered.min(line_range.end),
+                });
+            }
+        }
+        if line_range.end > covered || furthest.is_none() {
+            covered = covered.max(line_range.end);
+            furthest = Some(i);
+        }
+    }
+    if covered < line_count {
+        issues.push(ChunkIssue::Gap {
+            line_range: covered..line_count,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn chunks(line_ranges: Vec<Range<usize>>) -> Vec<Chunk> {
+        line_ranges
+            .into_iter()
+            .map(|line_range| Chunk::new(line_range, vec![]))
+            .collect()
+    }
+
+    #[rstest]
+    #[case(vec![0..2, 2..4], vec![])]
+    #[case(vec![0..1, 2..4], vec![ChunkIssue::Gap { line_range: 1..2 }])]
+    #[case(vec![0..3], vec![ChunkIssue::Gap { line_range: 3..4 }])]
+    #[case(vec![0..3, 2..4], vec![ChunkIssue::Overlap { chunks: (0, 1), line_range: 2..3 }])]
+    #[case(vec![0..4, 1..2, 2..3], vec![
+        ChunkIssue::Overlap { chunks: (0, 1), line_range: 1..2 },
+        ChunkIssue::Overlap { chunks: (0, 2), line_range: 2..3 },
+    ])]
+    #[case(vec![0..2, 2..5], vec![ChunkIssue::OutOfBounds { chunk: 1, line_range: 2..5 }])]
+    #[case(vec![0..4, 4..4], vec![ChunkIssue::Empty { chunk: 1 }])]
+    fn test_validate_chunks(
+        #[case] line_ranges: Vec<Range<usize>>,
+        #[case] expected: Vec<ChunkIssue>,
+    ) {
+        let code = "a\nb\nc\nd\n";
+        assert_eq!(validate_chunks(code, &chunks(line_ranges)), expected);
+    }
+}
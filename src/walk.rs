@@ -0,0 +1,345 @@
+//
+// walk.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+use crate::{
+    lang::Lang,
+    notebook,
+    splitter::repo_map::{
+        build_repo_map,
+        map_file,
+    },
+    Chunk,
+    RepoMap,
+    Result,
+    SplitError,
+    Splitter,
+};
+use ignore::{
+    overrides::OverrideBuilder,
+    WalkBuilder,
+    WalkState,
+};
+use std::{
+    fs::File,
+    io::Read,
+    panic::{
+        self,
+        AssertUnwindSafe,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::{
+        mpsc::{
+            self,
+            Receiver,
+        },
+        Arc,
+    },
+};
+
+/// Options controlling how a directory is walked by `split_dir` and `repo_map_dir`
+#[derive(Debug, Clone)]
+pub struct WalkOptions {
+    /// the number of worker threads, `0` picks a number based on the available CPUs
+    pub threads: usize,
+    /// the files larger than this size in bytes are skipped
+    pub max_file_size: Option<u64>,
+    /// respect the `.gitignore`, `.ignore` and `.git/info/exclude` files
+    pub respect_ignore_files: bool,
+    /// walk the hidden files and directories too
+    pub include_hidden: bool,
+    /// follow the symbolic links
+    pub follow_links: bool,
+    /// only the files matching one of these globs are split, e.g. `*.rs`
+    pub include: Vec<String>,
+    /// the files matching one of these globs are skipped
+    pub exclude: Vec<String>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            threads: 0,
+            max_file_size: None,
+            respect_ignore_files: true,
+            include_hidden: false,
+            follow_links: false,
+            include: vec![],
+            exclude: vec![],
+        }
+    }
+}
+
+/// The streamed results of `split_dir`, one item for every supported file and for
+/// every entry the walk can't read
+///
+/// Dropping the iterator stops the walk.
+pub struct SplitDirIter {
+    receiver: Receiver<(PathBuf, Result<Vec<Chunk>>)>,
+}
+
+impl Iterator for SplitDirIter {
+    type Item = (PathBuf, Result<Vec<Chunk>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// The map of a directory built by `repo_map_dir` and the files left out of it
+#[derive(Debug)]
+pub struct RepoMapResult {
+    /// the map of the files read and parsed
+    pub map: RepoMap,
+    /// the files that failed to read or to parse and the entries the walk can't read,
+    /// sorted by path
+    pub errors: Vec<(PathBuf, SplitError)>,
+}
+
+/// Walks the directory and splits every supported file in parallel
+///
+/// The language of every file is detected by the splitter, from its name and, for
+/// a file without a supported extension, from the shebang or the modeline of its
+/// first lines. The files of an unsupported language are skipped. A file that fails
+/// to split, even with a panic, yields an error for that file only and the walk goes
+/// on, so does an entry the walk can't read, e.g. an unreadable directory or a broken
+/// symbolic link.
+///
+/// # Arguments
+///
+/// * `root` - The directory to walk.
+/// * `splitter` - The splitter shared by the worker threads.
+/// * `options` - The options of the walk.
+///
+/// # Errors
+///
+/// Returns an error if one of the include or exclude globs is invalid.
+///
+/// # Example
+///
+/// ```no_run
+/// use devgen_splitter::{
+///     split_dir,
+///     SplitOptions,
+///     Splitter,
+///     WalkOptions,
+/// };
+/// use std::sync::Arc;
+///
+/// let splitter = Arc::new(
+///     Splitter::builder()
+///         .options(SplitOptions {
+///             chunk_line_limit: 40,
+///             ..Default::default()
+///         })
+///         .build(),
+/// );
+/// for (path, chunks) in split_dir("./src", splitter, &WalkOptions::default()).unwrap() {
+///     match chunks {
+///         Ok(chunks) => println!("{}: {} chunks", path.display(), chunks.len()),
+///         Err(e) => eprintln!("{}: {}", path.display(), e),
+///     }
+/// }
+/// ```
+pub fn split_dir<P: AsRef<Path>>(
+    root: P,
+    splitter: Arc<Splitter>,
+    options: &WalkOptions,
+) -> Result<SplitDirIter> {
+    let root = root.as_ref().to_path_buf();
+    let walker = walk_builder(&root, options)?.build_parallel();
+    let (sender, receiver) = mpsc::sync_channel(options.threads.max(1) * 4);
+    std::thread::spawn(move || {
+        walker.run(|| {
+            let sender = sender.clone();
+            let splitter = splitter.clone();
+            let root = root.clone();
+            Box::new(move |entry| {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        let (path, e) = walk_error(&root, e);
+                        return match sender.send((path, Err(e))) {
+                            Ok(()) => WalkState::Continue,
+                            Err(_) => WalkState::Quit,
+                        };
+                    }
+                };
+                if !entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_file())
+                {
+                    return WalkState::Continue;
+                }
+                let path = entry.into_path();
+                let supported = is_supported(&path, |filename, code| {
+                    (cfg!(feature = "notebook") && notebook::is_notebook(filename))
+                        || splitter.lang_config(filename, code).is_some()
+                });
+                if !supported {
+                    return WalkState::Continue;
+                }
+                let result = panic::catch_unwind(AssertUnwindSafe(|| splitter.split_path(&path)))
+                    .unwrap_or(Err(SplitError::Panicked));
+                match sender.send((path, result)) {
+                    Ok(()) => WalkState::Continue,
+                    // the iterator is dropped
+                    Err(_) => WalkState::Quit,
+                }
+            })
+        });
+    });
+    Ok(SplitDirIter { receiver })
+}
+
+/// the number of bytes read from a file without a supported extension to detect its
+/// language
+const HEAD_BYTES: u64 = 1024;
+
+/// Builds the map of every supported file of the directory in parallel, see `repo_map`
+///
+/// The directory is walked with the same options as `split_dir`, the paths of the map
+/// are relative to the directory. A file that fails to read or to parse, even with a
+/// panic, is left out of the map and reported in the errors, so is an entry the walk
+/// can't read.
+///
+/// # Errors
+///
+/// Returns an error if one of the include or exclude globs is invalid.
+///
+/// # Example
+///
+/// ```no_run
+/// use devgen_splitter::{
+///     repo_map_dir,
+///     ApproximateTokenizer,
+///     WalkOptions,
+/// };
+///
+/// let result = repo_map_dir("./src", &WalkOptions::default()).unwrap();
+/// for (path, e) in &result.errors {
+///     eprintln!("{}: {}", path.display(), e);
+/// }
+/// println!("{}", result.map.render(50, 1024, &ApproximateTokenizer));
+/// ```
+pub fn repo_map_dir<P: AsRef<Path>>(root: P, options: &WalkOptions) -> Result<RepoMapResult> {
+    let root = root.as_ref();
+    let walker = walk_builder(root, options)?.build_parallel();
+    let (sender, receiver) = mpsc::channel();
+    walker.run(|| {
+        let sender = sender.clone();
+        Box::new(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    let (path, e) = walk_error(root, e);
+                    let _ = sender.send((path, Err(e)));
+                    return WalkState::Continue;
+                }
+            };
+            if !entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+            {
+                return WalkState::Continue;
+            }
+            let path = entry.into_path();
+            if !is_supported(&path, |filename, code| {
+                Lang::detect(filename, code).is_some()
+            }) {
+                return WalkState::Continue;
+            }
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let result = std::fs::read_to_string(&path)
+                .map_err(SplitError::Io)
+                .and_then(|code| {
+                    panic::catch_unwind(AssertUnwindSafe(|| map_file(&relative, &code)))
+                        .unwrap_or(Err(SplitError::Panicked))
+                });
+            let _ = sender.send((path, result));
+            WalkState::Continue
+        })
+    });
+    drop(sender);
+    let mut files = vec![];
+    let mut errors = vec![];
+    for (path, result) in receiver {
+        match result {
+            Ok(file) => files.push(file),
+            Err(e) => errors.push((path, e)),
+        }
+    }
+    errors.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(RepoMapResult {
+        map: build_repo_map(files),
+        errors,
+    })
+}
+
+fn walk_builder(root: &Path, options: &WalkOptions) -> Result<WalkBuilder> {
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.include {
+        overrides.add(glob)?;
+    }
+    for glob in &options.exclude {
+        overrides.add(&format!("!{}", glob))?;
+    }
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .overrides(overrides.build()?)
+        .threads(options.threads)
+        .max_filesize(options.max_file_size)
+        .hidden(!options.include_hidden)
+        .follow_links(options.follow_links)
+        .git_ignore(options.respect_ignore_files)
+        .git_global(options.respect_ignore_files)
+        .git_exclude(options.respect_ignore_files)
+        .ignore(options.respect_ignore_files)
+        .parents(options.respect_ignore_files)
+        .require_git(false);
+    Ok(builder)
+}
+
+/// Returns the path of the entry the walk failed to read, the root if the error has no
+/// path, and the error, a `SplitError::Io` keeping the kind of an I/O error
+fn walk_error(root: &Path, e: ignore::Error) -> (PathBuf, SplitError) {
+    let path = error_path(&e).unwrap_or(root).to_path_buf();
+    let e = match e.io_error().map(|io| io.kind()) {
+        Some(kind) => SplitError::Io(std::io::Error::new(kind, e)),
+        None => SplitError::Walk(e),
+    };
+    (path, e)
+}
+
+fn error_path(e: &ignore::Error) -> Option<&Path> {
+    match e {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::Loop { child, .. } => Some(child),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            error_path(err)
+        }
+        ignore::Error::Partial(errors) => errors.first().and_then(error_path),
+        _ => None,
+    }
+}
+
+/// Returns true if the language of the file is supported, reading its first bytes only
+/// if the name isn't enough
+fn is_supported(path: &Path, supports: impl Fn(&str, &str) -> bool) -> bool {
+    let filename = path.to_string_lossy();
+    if supports(&filename, "") {
+        return true;
+    }
+    let mut head = vec![];
+    let read = File::open(path).and_then(|file| file.take(HEAD_BYTES).read_to_end(&mut head));
+    read.is_ok() && supports(&filename, &String::from_utf8_lossy(&head))
+}
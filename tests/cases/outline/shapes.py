@@ -0,0 +1,25 @@
+import math
+
+
+# a shape with an area
+class Shape:
+    def __init__(self, name):
+        # the name shown to the user
+        self.name = name
+
+    def area(self):
+        raise NotImplementedError
+
+
+class Circle(Shape):
+    def area(self):
+        return math.pi * self.radius**2
+
+
+def largest(shapes):
+    # the shapes are compared by their area
+    return max(shapes, key=lambda shape: shape.area())
+
+
+def describe(shape):
+    return f"{shape.name}: {shape.area()}"
@@ -0,0 +1,20 @@
+export interface Item {
+  id: number;
+  name: string;
+}
+
+export class Store {
+  private items: Item[] = [];
+
+  add(item: Item): void {
+    this.items.push(item);
+  }
+
+  find(id: number): Item | undefined {
+    return this.items.find((item) => item.id === id);
+  }
+}
+
+export function createStore(): Store {
+  return new Store();
+}
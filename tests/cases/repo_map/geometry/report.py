@@ -0,0 +1,9 @@
+from geometry.shapes import Circle, Shape
+
+
+def total_area(shapes):
+    return sum(shape.area() for shape in shapes)
+
+
+def describe(shape: Shape):
+    return f"{type(shape).__name__}: {shape.area()}"
@@ -0,0 +1,9 @@
+from geometry.report import describe, total_area
+from geometry.shapes import Circle
+
+
+def main():
+    circles = [Circle(1), Circle(2)]
+    print(total_area(circles))
+    for circle in circles:
+        print(describe(circle))
@@ -0,0 +1,15 @@
+import { sql } from "./db";
+
+export async function listOrders(userId: number) {
+  const rows = await sql`
+    SELECT o.id, o.total
+    FROM orders o
+    WHERE o.user_id = ${userId}
+  `;
+  return rows;
+}
+
+export function archive(db: Db) {
+  db.run("INSERT INTO archive (id) SELECT id FROM orders");
+  db.run("DELETE FROM orders");
+}
@@ -0,0 +1,33 @@
+import sqlite3
+
+
+class UserRepository:
+    """Select the users stored in the database."""
+
+    def __init__(self, path):
+        self.conn = sqlite3.connect(path)
+
+    def find(self, user_id):
+        cursor = self.conn.execute(
+            """
+            SELECT id, name
+            FROM users
+            WHERE id = ?
+            """,
+            (user_id,),
+        )
+        return cursor.fetchone()
+
+    def rename(self, user_id, name):
+        # language=sql
+        query = f"UPDATE {self.table} SET name = ? WHERE id = ?"
+        self.conn.execute(query, (name, user_id))
+
+
+def log(message):
+    print("select a user from the list")
+    print(message)
+
+
+def count(conn):
+    return conn.execute("SELECT COUNT(id) FROM users").fetchone()
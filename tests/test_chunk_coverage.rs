@@ -0,0 +1,69 @@
+#![cfg(all(
+    feature = "lang-bash",
+    feature = "lang-cpp",
+    feature = "lang-csharp",
+    feature = "lang-java",
+    feature = "lang-markdown",
+    feature = "lang-php",
+    feature = "lang-python",
+    feature = "lang-r",
+    feature = "lang-ruby",
+    feature = "lang-rust",
+    feature = "lang-sql",
+    feature = "lang-swift",
+    feature = "lang-toml",
+    feature = "lang-typescript",
+))]
+
+use devgen_splitter::{
+    split,
+    validate_chunks,
+    SplitOptions,
+    SplitStrategy,
+};
+use rstest::rstest;
+
+#[rstest]
+#[case("test_script.sh", include_str!("./cases/bash/test_script.sh"))]
+#[case("test.cc", include_str!("./cases/cpp/test.cc"))]
+#[case("Example.cs", include_str!("./cases/cs/Example.cs"))]
+#[case("test_java.java", include_str!("./cases/java/test_java.java"))]
+#[case("test.md", include_str!("./cases/md/test.md"))]
+#[case("example_script.php", include_str!("./cases/php/example_script.php"))]
+#[case("test_octogen.py", include_str!("./cases/python/test_octogen.py"))]
+#[case("Library.R", include_str!("./cases/r/Library.R"))]
+#[case("Package.r", include_str!("./cases/r/Package.r"))]
+#[case("example.rb", include_str!("./cases/ruby/example.rb"))]
+#[case("large_field.rs", include_str!("./cases/rust/large_field.rs"))]
+#[case("rust_function_in_mod.rs", include_str!("./cases/rust/rust_function_in_mod.rs"))]
+#[case("rust_function_test.rs", include_str!("./cases/rust/rust_function_test.rs"))]
+#[case("rust_long_function.rs", include_str!("./cases/rust/rust_long_function.rs"))]
+#[case("rust_method_for_incomplete_block.rs", include_str!("./cases/rust/rust_method_for_incomplete_block.rs"))]
+#[case("rust_similar_case.rs", include_str!("./cases/rust/rust_similar_case.rs"))]
+#[case("rust_tonic_case.rs", include_str!("./cases/rust/rust_tonic_case.rs"))]
+#[case("test_db3_meta.sol", include_str!("./cases/solidity/test_db3_meta.sol"))]
+#[case("test_sql.sql", include_str!("./cases/sql/test_sql.sql"))]
+#[case("test_sql_large_query.sql", include_str!("./cases/sql/test_sql_large_query.sql"))]
+#[case("GameView.swift", include_str!("./cases/swift/GameView.swift"))]
+#[case("test.swift", include_str!("./cases/swift/test.swift"))]
+#[case("test.toml", include_str!("./cases/toml/test.toml"))]
+#[case("typescript_function_class.ts", include_str!("./cases/ts/typescript_function_class.ts"))]
+#[case("typescript_function_test.ts", include_str!("./cases/ts/typescript_function_test.ts"))]
+#[case("typescript_react_test.ts", include_str!("./cases/ts/typescript_react_test.ts"))]
+fn test_chunks_tile_the_file(
+    #[case] filename: &str,
+    #[case] code: &str,
+    #[values(10, 30)] chunk_line_limit: usize,
+    #[values(None, Some(5))] min_chunk_lines: Option<usize>,
+    #[values(SplitStrategy::Greedy, SplitStrategy::Balanced)] strategy: SplitStrategy,
+) {
+    let options = SplitOptions {
+        chunk_line_limit,
+        min_chunk_lines,
+        strategy,
+        ..Default::default()
+    };
+    let chunks = split(filename, code, &options).unwrap();
+    let issues = validate_chunks(code, &chunks);
+    assert!(issues.is_empty(), "{}: {:?}", filename, issues);
+}
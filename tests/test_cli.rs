@@ -0,0 +1,124 @@
+#![cfg(all(feature = "cli", feature = "lang-rust"))]
+
+use rstest::rstest;
+use std::{
+    io::Write,
+    process::{
+        Command,
+        Stdio,
+    },
+};
+
+fn devgen_split(args: &[&str], stdin: Option<&str>) -> (bool, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_devgen-split"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut child_stdin = child.stdin.take().unwrap();
+    if let Some(stdin) = stdin {
+        child_stdin.write_all(stdin.as_bytes()).unwrap();
+    }
+    drop(child_stdin);
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[rstest]
+#[case(&["tests/cases/rust/rust_function_in_mod.rs"], 3)]
+#[case(&["--chunk-lines", "10", "tests/cases/rust/rust_long_function.rs"], 13)]
+#[case(&["--include", "*.java", "tests/cases"], 4)]
+fn test_cli_jsonl(#[case] args: &[&str], #[case] expected: usize) {
+    let (success, stdout) = devgen_split(args, None);
+    assert!(success);
+    let records = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(records.len(), expected);
+    for record in records {
+        assert!(record["path"].is_string());
+        assert!(record["line_range"]["end"].as_u64() > record["line_range"]["start"].as_u64());
+        assert!(record.get("text").is_none());
+    }
+}
+
+#[test]
+fn test_cli_stdin_with_text() {
+    let code = "fn main() {\n    println!(\"Hello, world!\");\n}\n";
+    let (success, stdout) = devgen_split(&["--lang", "rust", "--with-text"], Some(code));
+    assert!(success);
+    let record = serde_json::from_str::<serde_json::Value>(stdout.trim()).unwrap();
+    assert_eq!(record["path"], "-");
+    assert_eq!(record["text"], code.trim_end());
+    assert_eq!(record["entities"][0]["name"], "main");
+}
+
+#[test]
+fn test_cli_table_and_errors() {
+    let (success, stdout) = devgen_split(
+        &[
+            "--format",
+            "table",
+            "tests/cases/rust/rust_function_test.rs",
+            "missing.rs",
+        ],
+        None,
+    );
+    assert!(!success);
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert!(lines[0].starts_with("PATH"));
+    assert!(lines[1].contains("Function main"));
+}
+
+#[rstest]
+#[case("--overlap=3", true)]
+#[case("--overlap=0.1", true)]
+#[case("--overlap=1e-1", true)]
+#[case("--overlap=-0.5", false)]
+#[case("--overlap=inf", false)]
+#[case("--overlap=some", false)]
+fn test_cli_overlap(#[case] overlap: &str, #[case] expected: bool) {
+    let (success, stdout) =
+        devgen_split(&[overlap, "tests/cases/rust/rust_function_test.rs"], None);
+    assert_eq!(success, expected);
+    assert_eq!(!stdout.is_empty(), expected);
+}
+
+#[test]
+fn test_cli_stdin_requires_lang() {
+    let (success, stdout) = devgen_split(&[], Some("fn main() {}"));
+    assert!(!success);
+    assert!(stdout.is_empty());
+}
+
+#[cfg(feature = "lang-python")]
+#[test]
+fn test_cli_notebook_with_text() {
+    let (success, stdout) = devgen_split(
+        &[
+            "--with-text",
+            "--keep-notebook-outputs",
+            "tests/cases/notebook/analysis.ipynb",
+        ],
+        None,
+    );
+    assert!(success);
+    let records = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(records[1]["cell"]["index"], 1);
+    assert_eq!(records[1]["cell"]["cell_type"], "code");
+    assert!(records[1]["text"]
+        .as_str()
+        .unwrap()
+        .starts_with("import json"));
+    assert_eq!(records[2]["cell"]["output"], true);
+    assert_eq!(records[2]["text"], "loaded 3 rows");
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-cpp")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,7 +10,7 @@ use rstest::rstest;
 #[case(
     "test.cc",
     include_str!("./cases/cpp/test.cc"),
-    SplitOptions { chunk_line_limit: 20 },
+    SplitOptions { chunk_line_limit: 20, ..Default::default() },
     2
 )]
 fn test_cpp_split(
@@ -18,7 +20,7 @@ fn test_cpp_split(
     #[case] expected: usize,
 ) {
     let result = split(filename, code, &options);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
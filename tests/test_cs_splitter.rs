@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-csharp")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,7 +10,7 @@ use rstest::rstest;
 #[case(
     "example.cs",
     include_str!("./cases/cs/Example.cs"),
-    SplitOptions { chunk_line_limit: 10},
+    SplitOptions { chunk_line_limit: 10, ..Default::default() },
     3
 
 )]
@@ -20,7 +22,7 @@ fn test_cs_split(
 ) {
     let result = split(filename, code, &options);
     println!("result: {:?}", result);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
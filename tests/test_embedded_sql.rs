@@ -0,0 +1,80 @@
+//
+// test_embedded_sql.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(all(
+    feature = "lang-python",
+    feature = "lang-sql",
+    feature = "lang-typescript"
+))]
+
+use devgen_splitter::{
+    split,
+    validate_chunks,
+    EntityType,
+    SplitOptions,
+};
+use rstest::rstest;
+use std::ops::Range;
+
+#[rstest]
+#[case(
+    "repository.py",
+    include_str!("./cases/sql_embedded/repository.py"),
+    vec![
+        ("SELECT users", Some("find"), 12..14),
+        ("UPDATE", Some("rename"), 22..22),
+        ("SELECT users", Some("count"), 32..32),
+    ]
+)]
+#[case(
+    "orders.ts",
+    include_str!("./cases/sql_embedded/orders.ts"),
+    vec![
+        ("SELECT orders", Some("listOrders"), 4..6),
+        ("INSERT archive", Some("archive"), 12..12),
+        ("DELETE orders", Some("archive"), 13..13),
+    ]
+)]
+fn test_embedded_sql(
+    #[case] filename: &str,
+    #[case] code: &str,
+    #[case] expected: Vec<(&str, Option<&str>, Range<usize>)>,
+) {
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        embedded_sql: true,
+        ..Default::default()
+    };
+    let chunks = split(filename, code, &options).unwrap();
+    assert!(validate_chunks(code, &chunks).is_empty());
+    let queries = chunks
+        .iter()
+        .flat_map(|chunk| chunk.entities.iter())
+        .filter(|entity| entity.entity_type == EntityType::Query)
+        .map(|entity| {
+            (
+                entity.name.as_str(),
+                entity.parent.as_deref(),
+                entity.completed_line_range.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(queries, expected);
+}
+
+#[test]
+fn test_embedded_sql_disabled() {
+    let code = include_str!("./cases/sql_embedded/orders.ts");
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        ..Default::default()
+    };
+    let chunks = split("orders.ts", code, &options).unwrap();
+    assert!(chunks
+        .iter()
+        .flat_map(|chunk| chunk.entities.iter())
+        .all(|entity| entity.entity_type != EntityType::Query));
+}
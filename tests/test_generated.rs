@@ -0,0 +1,93 @@
+//
+// test_generated.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(all(feature = "lang-javascript", feature = "lang-rust"))]
+
+use devgen_splitter::{
+    detect_generated,
+    split,
+    split_with_diagnostics,
+    validate_chunks,
+    GeneratedKind,
+    GeneratedPolicy,
+    SplitOptions,
+};
+use rstest::rstest;
+
+fn minified_js() -> String {
+    let line = "function a(b){return b.map(function(c){return c+1}).filter(Boolean)};".repeat(10);
+    std::iter::repeat_n(line, 5)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[test]
+fn test_detect_generated_cases() {
+    let code = include_str!("./cases/rust/large_field.rs");
+    assert_eq!(
+        detect_generated("large_field.rs", code),
+        Some(GeneratedKind::Generated)
+    );
+    assert_eq!(
+        detect_generated("bundle.js", &minified_js()),
+        Some(GeneratedKind::Minified)
+    );
+    let code = include_str!("./cases/rust/rust_tonic_case.rs");
+    assert_eq!(detect_generated("rust_tonic_case.rs", code), None);
+}
+
+#[rstest]
+#[case(GeneratedPolicy::Ignore)]
+#[case(GeneratedPolicy::Skip)]
+#[case(GeneratedPolicy::Coarse)]
+#[case(GeneratedPolicy::Tag)]
+fn test_generated_policy(#[case] policy: GeneratedPolicy) {
+    let code = include_str!("./cases/rust/large_field.rs");
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        generated: policy,
+        ..Default::default()
+    };
+    let result = split_with_diagnostics("large_field.rs", code, &options).unwrap();
+    let lines = code.lines().count();
+    match policy {
+        GeneratedPolicy::Ignore => {
+            assert_eq!(result.diagnostics.generated, None);
+            assert!(result.chunks.iter().all(|chunk| !chunk.generated));
+        }
+        GeneratedPolicy::Skip => {
+            assert_eq!(result.diagnostics.generated, Some(GeneratedKind::Generated));
+            assert!(result.chunks.is_empty());
+        }
+        GeneratedPolicy::Coarse => {
+            assert_eq!(result.diagnostics.generated, Some(GeneratedKind::Generated));
+            assert!(result.diagnostics.line_fallback);
+            assert_eq!(result.chunks.len(), lines.div_ceil(40));
+            assert!(result.chunks.iter().all(|chunk| chunk.entities.is_empty()));
+        }
+        GeneratedPolicy::Tag => {
+            assert_eq!(result.diagnostics.generated, Some(GeneratedKind::Generated));
+            assert!(result.chunks.iter().all(|chunk| chunk.generated));
+        }
+    }
+    if policy != GeneratedPolicy::Skip {
+        assert!(validate_chunks(code, &result.chunks).is_empty());
+    }
+}
+
+#[test]
+fn test_minified_js_split_coarsely() {
+    let code = minified_js();
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        generated: GeneratedPolicy::Coarse,
+        ..Default::default()
+    };
+    let chunks = split("bundle.js", &code, &options).unwrap();
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].line_range, 0..5);
+    assert!(chunks[0].entities.is_empty());
+}
@@ -0,0 +1,218 @@
+//
+// test_imports.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(all(
+    feature = "lang-go",
+    feature = "lang-java",
+    feature = "lang-javascript",
+    feature = "lang-python",
+    feature = "lang-typescript"
+))]
+
+use devgen_splitter::{
+    split,
+    validate_chunks,
+    EntityType,
+    SplitOptions,
+};
+use rstest::rstest;
+
+#[rstest]
+#[case(
+    "test.py",
+    r#"import os.path
+import numpy as np
+from typing import List, Optional as Maybe
+from .models import *
+
+
+def first(paths):
+    return [os.path.basename(path) for path in paths]
+
+
+def second(values: List[int]) -> Maybe[int]:
+    array = np.array(values)
+    return array.max() if values else None
+"#,
+    4,
+    vec![
+        vec![("import os.path", vec!["os"])],
+        vec![
+            ("import numpy as np", vec!["np"]),
+            ("from typing import List, Optional as Maybe", vec!["List", "Maybe"]),
+        ],
+    ]
+)]
+#[case(
+    "test.ts",
+    r#"import Store, { load, save as persist } from "./store";
+import * as path from "path";
+import type { Options } from "./options";
+
+
+export function first(file: string): Store {
+    return load(path.join(".", file));
+}
+
+export function second(store: Store, options: Options) {
+    persist(store, options);
+}
+"#,
+    3,
+    vec![
+        vec![
+            ("import Store, { load, save as persist } from \"./store\";", vec!["Store", "load"]),
+            ("import * as path from \"path\";", vec!["path"]),
+        ],
+        vec![
+            ("import Store, { load, save as persist } from \"./store\";", vec!["Store", "persist"]),
+            ("import type { Options } from \"./options\";", vec!["Options"]),
+        ],
+    ]
+)]
+#[case(
+    "test.js",
+    r#"import Store, { load } from './store';
+import * as path from 'path';
+import './polyfills';
+
+
+export function first(file) {
+    return load(path.join('.', file));
+}
+
+export function second() {
+    return new Store();
+}
+"#,
+    3,
+    vec![
+        vec![
+            ("import Store, { load } from './store';", vec!["load"]),
+            ("import * as path from 'path';", vec!["path"]),
+        ],
+        vec![("import Store, { load } from './store';", vec!["Store"])],
+    ]
+)]
+#[case(
+    "Test.java",
+    r#"import java.util.List;
+import java.util.Map;
+import static java.util.Collections.emptyList;
+
+public class Test {
+    public List<String> first() {
+        return emptyList();
+    }
+
+    public Map<String, String> second() {
+        return null;
+    }
+}
+"#,
+    3,
+    vec![
+        vec![
+            ("import java.util.List;", vec!["List"]),
+            ("import static java.util.Collections.emptyList;", vec!["emptyList"]),
+        ],
+        vec![("import java.util.Map;", vec!["Map"])],
+    ]
+)]
+#[case(
+    "test.go",
+    r#"package main
+
+import (
+	"fmt"
+	str "strings"
+	"net/http"
+)
+
+func first(name string) {
+	fmt.Println(str.ToUpper(name))
+}
+
+func second(w http.ResponseWriter) {
+	fmt.Fprintln(w, "ok")
+}
+"#,
+    3,
+    vec![
+        vec![("\"fmt\"", vec!["fmt"]), ("str \"strings\"", vec!["str"])],
+        vec![("\"fmt\"", vec!["fmt"]), ("\"net/http\"", vec!["http"])],
+    ]
+)]
+fn test_imports(
+    #[case] filename: &str,
+    #[case] code: &str,
+    #[case] import_count: usize,
+    #[case] expected: Vec<Vec<(&str, Vec<&str>)>>,
+) {
+    let options = SplitOptions {
+        chunk_line_limit: 4,
+        collect_imports: true,
+        ..Default::default()
+    };
+    let chunks = split(filename, code, &options).unwrap();
+    assert!(validate_chunks(code, &chunks).is_empty());
+    let imports = chunks[0]
+        .entities
+        .iter()
+        .filter(|entity| entity.entity_type == EntityType::Import)
+        .count();
+    assert_eq!(imports, import_count);
+    assert!(chunks[0].imports.is_empty());
+    let attached = chunks[1..]
+        .iter()
+        .map(|chunk| {
+            chunk
+                .imports
+                .iter()
+                .map(|import| {
+                    (
+                        import.statement.as_str(),
+                        import.names.iter().map(String::as_str).collect(),
+                    )
+                })
+                .collect::<Vec<(&str, Vec<&str>)>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(attached, expected);
+}
+
+#[test]
+fn test_imports_disabled() {
+    let code = "import os\n\n\ndef first():\n    return os.getcwd()\n";
+    let options = SplitOptions {
+        chunk_line_limit: 4,
+        ..Default::default()
+    };
+    let chunks = split("test.py", code, &options).unwrap();
+    assert!(chunks.iter().all(|chunk| chunk.imports.is_empty()
+        && chunk
+            .entities
+            .iter()
+            .all(|entity| entity.entity_type != EntityType::Import)));
+}
+
+#[test]
+fn test_imports_keep_the_ids() {
+    let code = "import os\n\n\ndef first():\n    return os.getcwd()\n";
+    let ids = |collect_imports| {
+        let options = SplitOptions {
+            chunk_line_limit: 4,
+            collect_imports,
+            ..Default::default()
+        };
+        split("test.py", code, &options)
+            .unwrap()
+            .into_iter()
+            .map(|chunk| chunk.id)
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(ids(true), ids(false));
+}
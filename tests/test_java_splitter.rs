@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-java")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,7 +10,7 @@ use rstest::rstest;
 #[case(
     "java_function_test.java",
     include_str!("./cases/java/test_java.java"),
-    SplitOptions { chunk_line_limit: 40 },
+    SplitOptions { chunk_line_limit: 40, ..Default::default() },
     4
 )]
 fn test_java_split(
@@ -18,7 +20,7 @@ fn test_java_split(
     #[case] expected: usize,
 ) {
     let result = split(filename, code, &options);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
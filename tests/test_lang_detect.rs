@@ -0,0 +1,69 @@
+//
+// test_lang_detect.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(all(
+    feature = "lang-bash",
+    feature = "lang-c",
+    feature = "lang-cpp",
+    feature = "lang-javascript",
+    feature = "lang-python",
+    feature = "lang-ruby",
+    feature = "lang-rust",
+    feature = "lang-toml"
+))]
+
+use devgen_splitter::{
+    split,
+    Lang,
+    SplitOptions,
+};
+use rstest::rstest;
+
+#[rstest]
+#[case(
+    "bin/deploy",
+    "#!/usr/bin/env bash\nset -e\necho deploy\n",
+    Some("Bash")
+)]
+#[case("scripts/run", "#!/usr/bin/python3.11\nprint('run')\n", Some("Python"))]
+#[case(
+    "tools/serve",
+    "#!/usr/bin/env node\nconsole.log(1);\n",
+    Some("JavaScript")
+)]
+#[case("build.txt", "# vim: set filetype=python :\nx = 1\n", Some("Python"))]
+#[case("config", "x = 1\ny = 2\n# -*- mode: ruby -*-\n", Some("Ruby"))]
+#[case("Gemfile", "source 'https://rubygems.org'\n", Some("Ruby"))]
+#[case("project/Pipfile", "[packages]\nrequests = \"*\"\n", Some("TOML"))]
+#[case(
+    "vector.h",
+    "#include <vector>\nclass Vector {\npublic:\n  int size();\n};\n",
+    Some("C++")
+)]
+#[case(
+    "stack.h",
+    "#include <stdlib.h>\ntypedef struct stack stack_t;\n",
+    Some("C")
+)]
+#[case("main.rs", "#!/usr/bin/env bash\n", Some("Bash"))]
+#[case("main.rs", "#![allow(dead_code)]\nfn main() {}\n", Some("Rust"))]
+#[case("notes", "just some notes\n", None)]
+fn test_lang_detect(#[case] filename: &str, #[case] code: &str, #[case] expected: Option<&str>) {
+    let lang_config = Lang::detect(filename, code);
+    assert_eq!(lang_config.map(|config| config.lang[0]), expected);
+}
+
+#[test]
+fn test_split_extensionless_script() {
+    let code = "#!/bin/sh\ndeploy() {\n  echo deploy\n}\ndeploy\n";
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        ..Default::default()
+    };
+    let chunks = split("bin/deploy", code, &options).unwrap();
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].line_range, 0..5);
+}
@@ -0,0 +1,122 @@
+//
+// test_lang_registry.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(all(feature = "lang-go", feature = "lang-java", feature = "lang-rust"))]
+
+use devgen_splitter::{
+    split,
+    split_with_diagnostics,
+    Chunk,
+    Lang,
+    LangConfig,
+    SplitError,
+    SplitOptions,
+    Splitter,
+};
+use std::sync::{
+    Mutex,
+    MutexGuard,
+};
+
+/// the registry is global to the test binary, the tests changing it run one at a time
+static REGISTRY: Mutex<()> = Mutex::new(());
+
+fn lock_registry() -> MutexGuard<'static, ()> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[test]
+fn test_register_new_language() {
+    let _registry = lock_registry();
+    let rust = Lang::from_name("rust").unwrap();
+    let config = LangConfig::new("RustScript", rust.grammar, &["rss"], rust.query);
+    let registered = Lang::register(config).unwrap();
+    assert_eq!(registered.name(), "RustScript");
+    assert_eq!(
+        Lang::from_filename("main.rss").unwrap().name(),
+        "RustScript"
+    );
+    assert_eq!(Lang::from_name("rustscript").unwrap().name(), "RustScript");
+    assert!(Lang::all().iter().any(|lang| lang.name() == "RustScript"));
+
+    let code = include_str!("./cases/rust/rust_function_test.rs");
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        ..Default::default()
+    };
+    let chunks = split("main.rss", code, &options).unwrap();
+    // the same chunks as a Rust file, only the ids depend on the file name
+    let expected = split("main.rs", code, &options).unwrap();
+    let ranges = |chunks: &[Chunk]| {
+        chunks
+            .iter()
+            .map(|chunk| chunk.line_range.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ranges(&chunks), ranges(&expected));
+    assert!(chunks.iter().any(|chunk| !chunk.entities.is_empty()));
+    let splitter = Splitter::builder().options(options).build();
+    assert_eq!(
+        splitter.split("main.rss", code).unwrap().len(),
+        chunks.len()
+    );
+}
+
+#[test]
+fn test_replace_bundled_language() {
+    let _registry = lock_registry();
+    let code = include_str!("./cases/java/test_java.java");
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        ..Default::default()
+    };
+    let result = split_with_diagnostics("Test.java", code, &options).unwrap();
+    assert!(!result.diagnostics.line_fallback);
+
+    // an empty query splits the files by their syntax nodes only
+    let java = Lang::from_name("java").unwrap();
+    Lang::register(java.with_query("").with_file_extensions(&["java", "jav"])).unwrap();
+    let result = split_with_diagnostics("Test.java", code, &options).unwrap();
+    assert!(result.diagnostics.line_fallback);
+    assert!(result.chunks.iter().all(|chunk| chunk.entities.is_empty()));
+    assert_eq!(Lang::from_filename("Test.jav").unwrap().name(), "Java");
+    assert_eq!(
+        Lang::all()
+            .iter()
+            .filter(|lang| lang.name() == "Java")
+            .count(),
+        1
+    );
+    // restore the bundled config for the other tests
+    Lang::register(java.with_query(java.query)).unwrap();
+    let result = split_with_diagnostics("Test.java", code, &options).unwrap();
+    assert!(!result.diagnostics.line_fallback);
+    assert!(Lang::from_filename("Test.jav").is_none());
+}
+
+#[test]
+fn test_register_header_language() {
+    let _registry = lock_registry();
+    // the registered language is used for the headers instead of C or C++
+    let rust = Lang::from_name("rust").unwrap();
+    let config = LangConfig::new("RustHeader", rust.grammar, &["h"], rust.query);
+    Lang::register(config).unwrap();
+    let code = "namespace util {\nclass Vector;\n}\n";
+    assert_eq!(Lang::detect("vector.h", code).unwrap().name(), "RustHeader");
+    assert_eq!(
+        Lang::from_filename("vector.h").unwrap().name(),
+        "RustHeader"
+    );
+}
+
+#[test]
+fn test_register_invalid_query() {
+    let _registry = lock_registry();
+    let go = Lang::from_name("go").unwrap();
+    let result = Lang::register(go.with_query("(no_such_node) @function.definition"));
+    assert!(matches!(result, Err(SplitError::QueryError { .. })));
+    assert_eq!(Lang::from_name("go").unwrap().query, go.query);
+}
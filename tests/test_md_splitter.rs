@@ -1,5 +1,13 @@
+#![cfg(all(
+    feature = "lang-markdown",
+    feature = "lang-python",
+    feature = "lang-rust"
+))]
+
 use devgen_splitter::{
     split,
+    split_with_diagnostics,
+    validate_chunks,
     SplitOptions,
 };
 use rstest::rstest;
@@ -8,7 +16,7 @@ use rstest::rstest;
 #[case(
     "interop/data/README.md",
     include_str!("./cases/md/test.md"),
-    SplitOptions { chunk_line_limit: 30 },
+    SplitOptions { chunk_line_limit: 30, ..Default::default() },
     1
 )]
 fn test_md_split(
@@ -18,7 +26,7 @@ fn test_md_split(
     #[case] expected: usize,
 ) {
     let result = split(filename, code, &options);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
@@ -28,3 +36,56 @@ fn test_md_split(
     }
     assert_eq!(result.len(), expected);
 }
+
+#[test]
+fn test_md_split_code_blocks() {
+    let code = include_str!("./cases/md/test_code_blocks.md");
+    let options = SplitOptions {
+        chunk_line_limit: 30,
+        ..Default::default()
+    };
+    let result = split_with_diagnostics("guide.md", code, &options).unwrap();
+    assert!(validate_chunks(code, &result.chunks).is_empty());
+    assert!(!result.diagnostics.line_fallback);
+    let lines = code.lines().collect::<Vec<&str>>();
+
+    let rust = result
+        .chunks
+        .iter()
+        .filter(|chunk| chunk.lang.as_deref() == Some("Rust"))
+        .collect::<Vec<_>>();
+    assert_eq!(rust.first().unwrap().line_range.start, 10);
+    assert_eq!(rust.last().unwrap().line_range.end, 25);
+    assert_eq!(rust[0].headings, vec!["Splitter Guide", "Usage", "Rust"]);
+    let functions = rust
+        .iter()
+        .flat_map(|chunk| chunk.entities.iter())
+        .map(|entity| (entity.name.as_str(), entity.completed_line_range.clone()))
+        .collect::<Vec<_>>();
+    assert!(functions.contains(&("split_file", 13..19)));
+    assert!(lines[13].starts_with("fn split_file"));
+
+    let python = result
+        .chunks
+        .iter()
+        .filter(|chunk| chunk.lang.as_deref() == Some("Python"))
+        .collect::<Vec<_>>();
+    assert_eq!(python.len(), 1);
+    assert_eq!(
+        python[0].headings,
+        vec!["Splitter Guide", "Usage", "Python"]
+    );
+    let entities = python[0]
+        .entities
+        .iter()
+        .map(|entity| (entity.name.as_str(), entity.parent.as_deref()))
+        .collect::<Vec<_>>();
+    assert!(entities.contains(&("fetch", Some("Client"))));
+
+    // the blocks without a supported language stay in the Markdown chunks
+    assert!(result
+        .chunks
+        .iter()
+        .filter(|chunk| chunk.line_range.end > 40)
+        .all(|chunk| chunk.lang.is_none()));
+}
@@ -0,0 +1,245 @@
+//
+// test_notebook.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(all(
+    feature = "notebook",
+    feature = "lang-markdown",
+    feature = "lang-python",
+    feature = "lang-r"
+))]
+
+use devgen_splitter::{
+    split,
+    split_with_diagnostics,
+    validate_notebook_chunks,
+    CellIssue,
+    CellType,
+    ChunkIssue,
+    Limit,
+    Notebook,
+    SplitError,
+    SplitLimits,
+    SplitOptions,
+    Splitter,
+};
+
+const ANALYSIS: &str = include_str!("./cases/notebook/analysis.ipynb");
+
+#[test]
+fn test_notebook_split_by_cell() {
+    let options = SplitOptions {
+        chunk_line_limit: 20,
+        ..Default::default()
+    };
+    let chunks = split("analysis.ipynb", ANALYSIS, &options).unwrap();
+    let cells = chunks
+        .iter()
+        .map(|chunk| {
+            let cell = chunk.cell.as_ref().unwrap();
+            (
+                cell.index,
+                cell.cell_type,
+                cell.output,
+                chunk.lang.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        cells,
+        vec![
+            (0, CellType::Markdown, false, Some("Markdown")),
+            (1, CellType::Code, false, Some("Python")),
+            (2, CellType::Code, false, Some("Python")),
+            (3, CellType::Raw, false, None),
+        ]
+    );
+    assert_eq!(chunks[1].entities[0].name, "load");
+    assert_eq!(chunks[2].entities[0].parent.as_deref(), Some("Model"));
+    // the chunks tile the source of their cell
+    let notebook = Notebook::parse(ANALYSIS).unwrap();
+    assert!(validate_notebook_chunks(&notebook, &chunks).is_empty());
+    let mut broken = chunks.clone();
+    broken[1].cell.as_mut().unwrap().line_range.end -= 1;
+    assert_eq!(
+        validate_notebook_chunks(&notebook, &broken),
+        vec![CellIssue {
+            cell: 1,
+            output: false,
+            issue: ChunkIssue::Gap { line_range: 7..8 },
+        }]
+    );
+}
+
+#[test]
+fn test_notebook_line_ranges() {
+    let options = SplitOptions {
+        chunk_line_limit: 20,
+        ..Default::default()
+    };
+    let chunks = split("analysis.ipynb", ANALYSIS, &options).unwrap();
+    let lines = ANALYSIS.lines().collect::<Vec<&str>>();
+    // the line ranges are the lines of the notebook file holding the source
+    assert_eq!(chunks[1].line_range, 25..33);
+    assert_eq!(chunks[1].cell.as_ref().unwrap().line_range, 0..8);
+    assert_eq!(lines[25].trim(), "\"import json\\n\",");
+    let load = &chunks[1].entities[0];
+    assert_eq!(
+        lines[load.completed_line_range.start].trim(),
+        "\"def load(path):\\n\","
+    );
+    assert_eq!(
+        lines[load.completed_line_range.end].trim(),
+        "\"    return rows\""
+    );
+    // the ids are unique within the notebook
+    let ids = chunks
+        .iter()
+        .map(|chunk| chunk.id.as_str())
+        .collect::<std::collections::HashSet<&str>>();
+    assert_eq!(ids.len(), chunks.len());
+}
+
+#[test]
+fn test_notebook_ids_of_moved_cells() {
+    let options = SplitOptions {
+        chunk_line_limit: 20,
+        ..Default::default()
+    };
+    let chunks = split("analysis.ipynb", ANALYSIS, &options).unwrap();
+    // a cell inserted before the others shifts their index but keeps their ids
+    let code = ANALYSIS.replacen(
+        "\"cells\": [\n",
+        "\"cells\": [\n  {\"cell_type\": \"raw\", \"metadata\": {}, \"source\": [\"todo\"]},\n",
+        1,
+    );
+    let moved = split("analysis.ipynb", &code, &options).unwrap();
+    assert_eq!(moved.len(), chunks.len() + 1);
+    for (chunk, moved) in chunks.iter().zip(&moved[1..]) {
+        assert_eq!(
+            moved.cell.as_ref().unwrap().index,
+            chunk.cell.as_ref().unwrap().index + 1
+        );
+        assert_eq!(moved.id, chunk.id);
+    }
+}
+
+#[test]
+fn test_notebook_file_bytes_limit() {
+    let options = SplitOptions {
+        limits: SplitLimits {
+            max_file_bytes: Some(100),
+            ..Default::default()
+        },
+        chunk_line_limit: 20,
+        ..Default::default()
+    };
+    let result = split("analysis.ipynb", ANALYSIS, &options);
+    assert!(matches!(
+        result,
+        Err(SplitError::LimitExceeded {
+            limit: Limit::FileBytes
+        })
+    ));
+}
+
+#[test]
+fn test_notebook_outputs() {
+    let options = SplitOptions {
+        keep_notebook_outputs: true,
+        chunk_line_limit: 20,
+        ..Default::default()
+    };
+    let chunks = split("analysis.ipynb", ANALYSIS, &options).unwrap();
+    let notebook = Notebook::parse(ANALYSIS).unwrap();
+    let outputs = chunks
+        .iter()
+        .filter(|chunk| chunk.cell.as_ref().unwrap().output)
+        .map(|chunk| notebook.text(chunk.cell.as_ref().unwrap()).unwrap())
+        .collect::<Vec<&str>>();
+    assert_eq!(outputs, vec!["loaded 3 rows", "0.5"]);
+    assert_eq!(chunks.len(), 6);
+    assert!(validate_notebook_chunks(&notebook, &chunks).is_empty());
+}
+
+#[test]
+fn test_notebook_kernel_language() {
+    let options = SplitOptions {
+        chunk_line_limit: 20,
+        ..Default::default()
+    };
+    let code = include_str!("./cases/notebook/stats.ipynb");
+    let result = split_with_diagnostics("stats.ipynb", code, &options).unwrap();
+    assert_eq!(result.chunks.len(), 1);
+    assert_eq!(result.chunks[0].lang.as_deref(), Some("R"));
+    assert_eq!(result.chunks[0].cell.as_ref().unwrap().line_range, 0..3);
+}
+
+#[test]
+fn test_notebook_diagnostics() {
+    let options = SplitOptions {
+        chunk_line_limit: 20,
+        tolerate_errors: true,
+        ..Default::default()
+    };
+    let code = r##"{
+ "cells": [
+  {
+   "cell_type": "raw",
+   "metadata": {},
+   "source": ["broken"]
+  },
+  {
+   "cell_type": "code",
+   "metadata": {},
+   "outputs": [],
+   "source": [
+    "def ok():\n",
+    "    return 1\n",
+    "\n",
+    "def broken(:\n",
+    "    return 2"
+   ]
+  }
+ ],
+ "metadata": {"kernelspec": {"language": "python"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+    let result = split_with_diagnostics("broken.ipynb", code, &options).unwrap();
+    let lines = code.lines().collect::<Vec<&str>>();
+    // the errors are in the lines of the notebook file and tell their cell
+    let errors = &result.diagnostics.syntax_errors;
+    assert!(!errors.is_empty());
+    for error in errors {
+        assert_eq!(error.cell, Some(1));
+        assert!(error.line_range.start >= 12 && error.line_range.end <= 17);
+    }
+    assert!(lines[15].contains("def broken"));
+    assert!(errors.iter().any(|error| error.line_range == (15..16)));
+    assert_eq!(result.diagnostics.masked_line_ranges, vec![15..16]);
+}
+
+#[test]
+fn test_notebook_splitter() {
+    let options = SplitOptions {
+        chunk_line_limit: 20,
+        ..Default::default()
+    };
+    let splitter = Splitter::builder().options(options).build();
+    let chunks = splitter.split("analysis.ipynb", ANALYSIS).unwrap();
+    assert_eq!(chunks.len(), 4);
+    assert!(chunks.iter().all(|chunk| !chunk.id.is_empty()));
+}
+
+#[test]
+fn test_invalid_notebook() {
+    let options = SplitOptions {
+        chunk_line_limit: 20,
+        ..Default::default()
+    };
+    let result = split("broken.ipynb", "{\"cells\": ", &options);
+    assert!(matches!(result, Err(SplitError::InvalidNotebook { .. })));
+}
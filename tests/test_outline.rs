@@ -0,0 +1,83 @@
+//
+// test_outline.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(all(
+    feature = "lang-python",
+    feature = "lang-rust",
+    feature = "lang-typescript"
+))]
+
+use devgen_splitter::{
+    outline,
+    ApproximateTokenizer,
+    EntityType,
+    SplitError,
+};
+
+#[test]
+fn test_outline_python() {
+    let code = include_str!("./cases/outline/shapes.py");
+    let outline = outline("shapes.py", code).unwrap();
+    assert_eq!(
+        outline.text,
+        "class Shape:\n    def __init__(self, name): ...\n    def area(self): ...\nclass Circle(Shape):\n    def area(self): ...\ndef largest(shapes): ...\ndef describe(shape): ..."
+    );
+    let names = outline
+        .items
+        .iter()
+        .map(|item| {
+            (
+                item.name.as_str(),
+                item.entity_type.clone(),
+                item.children.len(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("Shape", None, 2),
+            ("Circle", None, 1),
+            ("largest", Some(EntityType::Function), 0),
+            ("describe", Some(EntityType::Function), 0),
+        ]
+    );
+    assert_eq!(outline.items[0].children[1].line_range, 9..10);
+}
+
+#[test]
+fn test_outline_typescript() {
+    let code = include_str!("./cases/outline/store.ts");
+    let outline = outline("store.ts", code).unwrap();
+    assert_eq!(
+        outline.text,
+        "export interface Item { ... }\nexport class Store {\n    add(item: Item): void { ... }\n    find(id: number): Item | undefined { ... }\n}\nexport function createStore(): Store { ... }"
+    );
+}
+
+#[test]
+fn test_outline_budget() {
+    let code = include_str!("./cases/outline/shapes.py");
+    let outline = outline("shapes.py", code).unwrap();
+    // `Shape` and its `area` are referenced the most
+    assert_eq!(
+        outline.render(12, &ApproximateTokenizer),
+        "class Shape:\n    def area(self): ..."
+    );
+    assert_eq!(outline.render(0, &ApproximateTokenizer), "");
+    assert_eq!(
+        outline.render(usize::MAX, &ApproximateTokenizer),
+        outline.text
+    );
+}
+
+#[test]
+fn test_outline_unsupported_language() {
+    assert!(matches!(
+        outline("notes.unknown", "text"),
+        Err(SplitError::UnsupportedLanguage { .. })
+    ));
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-python")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,7 +10,7 @@ use rstest::rstest;
 #[case(
     "python_function_test.py",
     include_str!("./cases/python/test_octogen.py"),
-    SplitOptions { chunk_line_limit: 40},
+    SplitOptions { chunk_line_limit: 40, ..Default::default() },
     12
 )]
 fn test_python_splitter(
@@ -19,7 +21,7 @@ fn test_python_splitter(
 ) {
     let result = split(filename, code, &options);
     println!("result: {:?}", result);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-r")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,13 +10,13 @@ use rstest::rstest;
 #[case(
     "test.R",
     include_str!("./cases/r/Library.R"),
-    SplitOptions { chunk_line_limit: 10},
+    SplitOptions { chunk_line_limit: 10, ..Default::default() },
     82
 )]
 #[case(
     "test.r",
     include_str!("./cases/r/Package.r"),
-    SplitOptions { chunk_line_limit: 10},
+    SplitOptions { chunk_line_limit: 10, ..Default::default() },
     6
 )]
 fn test_r_split(
@@ -25,7 +27,7 @@ fn test_r_split(
 ) {
     let result = split(filename, code, &options);
     println!("result: {:?}", result);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
@@ -0,0 +1,123 @@
+//
+// test_rechunk.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(feature = "lang-rust")]
+
+use devgen_splitter::{
+    rechunk,
+    rechunk_with_diff,
+    split,
+    validate_chunks,
+    Chunk,
+    SplitOptions,
+};
+use rstest::rstest;
+
+fn sorted(mut chunks: Vec<Chunk>) -> Vec<Chunk> {
+    chunks.sort_by_key(|chunk| chunk.line_range.start);
+    chunks
+}
+
+#[rstest]
+#[case("rust_similar_case.rs", include_str!("./cases/rust/rust_similar_case.rs"), 100)]
+#[case("rust_tonic_case.rs", include_str!("./cases/rust/rust_tonic_case.rs"), 200)]
+fn test_rechunk_changed_line(#[case] filename: &str, #[case] code: &str, #[case] line: usize) {
+    let options = SplitOptions {
+        chunk_line_limit: 30,
+        ..Default::default()
+    };
+    let previous = split(filename, code, &options).unwrap();
+    let mut lines = code.lines().collect::<Vec<&str>>();
+    let old_line = lines[line];
+    lines[line] = "    // a changed line";
+    lines.insert(line + 1, "    // an inserted line");
+    let new_code = lines.join("\n") + "\n";
+    let diff = rechunk(filename, code, &new_code, &previous, &options).unwrap();
+    assert!(!diff.added.is_empty());
+    assert!(!diff.removed.is_empty());
+    assert!(diff.unchanged.len() > diff.added.len());
+    for chunk in &diff.removed {
+        assert!(chunk.line_range.start <= line + 1);
+    }
+    let chunks = sorted(
+        diff.unchanged
+            .iter()
+            .chain(diff.added.iter())
+            .cloned()
+            .collect(),
+    );
+    assert_eq!(chunks, split(filename, &new_code, &options).unwrap());
+    assert!(validate_chunks(&new_code, &chunks).is_empty());
+
+    let unified_diff = format!(
+        "--- a/{filename}\n+++ b/{filename}\n@@ -{start},3 +{start},4 @@\n {}\n-{}\n+{}\n+{}\n {}\n",
+        lines[line - 1],
+        old_line,
+        lines[line],
+        lines[line + 1],
+        lines[line + 2],
+        start = line,
+    );
+    let diff_from_patch =
+        rechunk_with_diff(filename, &new_code, &unified_diff, &previous, &options).unwrap();
+    assert_eq!(diff_from_patch, diff);
+}
+
+#[test]
+fn test_rechunk_unchanged_file() {
+    let code = include_str!("./cases/rust/rust_long_function.rs");
+    let options = SplitOptions {
+        chunk_line_limit: 30,
+        ..Default::default()
+    };
+    let previous = split("rust_long_function.rs", code, &options).unwrap();
+    let diff = rechunk("rust_long_function.rs", code, code, &previous, &options).unwrap();
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.unchanged, previous);
+}
+
+#[test]
+fn test_rechunk_references_and_imports() {
+    let code = r#"use std::collections::HashMap;
+
+fn first() -> HashMap<String, usize> {
+    HashMap::new()
+}
+
+fn second() -> HashMap<String, usize> {
+    let counts = first();
+    counts
+}
+"#;
+    let options = SplitOptions {
+        chunk_line_limit: 5,
+        collect_references: true,
+        collect_imports: true,
+        ..Default::default()
+    };
+    let previous = split("main.rs", code, &options).unwrap();
+    assert_eq!(previous.len(), 2);
+    assert!(!previous[1].imports.is_empty());
+    let new_code = code.replace(
+        "    HashMap::new()",
+        "    // a new line\n    HashMap::new()",
+    );
+    let diff = rechunk("main.rs", code, &new_code, &previous, &options).unwrap();
+    // the chunk of `second` only moves one line down
+    assert_eq!(diff.unchanged.len(), 1);
+    assert_eq!(diff.unchanged[0].line_range, 7..11);
+    assert_eq!(diff.unchanged[0].references[0].line, 7);
+    assert_eq!(diff.unchanged[0].imports[0].line_range, 0..0);
+    let chunks = sorted(
+        diff.unchanged
+            .iter()
+            .chain(diff.added.iter())
+            .cloned()
+            .collect(),
+    );
+    assert_eq!(chunks, split("main.rs", &new_code, &options).unwrap());
+}
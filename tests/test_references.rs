@@ -0,0 +1,195 @@
+//
+// test_references.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(all(
+    feature = "lang-go",
+    feature = "lang-java",
+    feature = "lang-javascript",
+    feature = "lang-python",
+    feature = "lang-typescript"
+))]
+
+use devgen_splitter::{
+    split,
+    ReferenceKind,
+    SplitOptions,
+};
+use rstest::rstest;
+
+/// Returns the references of every chunk of the code, as `kind name` strings
+fn references(filename: &str, code: &str) -> Vec<Vec<String>> {
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        collect_references: true,
+        ..Default::default()
+    };
+    split(filename, code, &options)
+        .unwrap()
+        .iter()
+        .map(|chunk| {
+            chunk
+                .references
+                .iter()
+                .map(|reference| format!("{:?} {}", reference.kind, reference.name))
+                .collect()
+        })
+        .collect()
+}
+
+#[rstest]
+#[case(
+    "test.py",
+    r#"import os.path
+from typing import List
+
+class Repo(Base):
+    def load(self, paths: List) -> Store:
+        store = Store()
+        for path in paths:
+            store.add(os.path.join(path))
+        return store
+"#,
+    vec![
+        "Module os.path",
+        "Module typing",
+        "Type Base",
+        "Type List",
+        "Type Store",
+        "Call Store",
+        "Call add",
+        "Call join",
+    ]
+)]
+#[case(
+    "test.ts",
+    r#"import { Store } from "./store";
+
+interface Loader {
+    load(path: string): Store;
+}
+
+class FileLoader extends Base implements Loader {
+    load(path: string): Store {
+        const store = new Store();
+        store.add(readFile(path));
+        return store;
+    }
+}
+"#,
+    vec![
+        "Module ./store",
+        "Type Store",
+        "Type Base",
+        "Type Loader",
+        "Call add",
+        "Call readFile",
+    ]
+)]
+#[case(
+    "test.js",
+    r#"import { Store } from './store';
+
+class FileLoader extends Base {
+    load(path) {
+        const store = new Store();
+        store.add(readFile(path));
+        return store;
+    }
+}
+"#,
+    vec![
+        "Module ./store",
+        "Type Base",
+        "Type Store",
+        "Call add",
+        "Call readFile",
+    ]
+)]
+#[case(
+    "Test.java",
+    r#"import java.util.List;
+
+public class Repo {
+    public Store load(List<String> paths) {
+        Store store = new Store();
+        store.add(Paths.get(paths.get(0)));
+        return store;
+    }
+}
+"#,
+    vec![
+        "Module java.util.List",
+        "Type Store",
+        "Type List",
+        "Type String",
+        "Call add",
+        "Call get",
+    ]
+)]
+#[case(
+    "test.go",
+    r#"package main
+
+import "fmt"
+
+type Store struct {
+	items []Item
+}
+
+func load(path string) *Store {
+	store := NewStore()
+	fmt.Println(path)
+	return store
+}
+"#,
+    vec![
+        "Module fmt",
+        "Type Item",
+        // the predeclared types are type identifiers too
+        "Type string",
+        "Type Store",
+        "Call NewStore",
+        "Call Println",
+    ]
+)]
+fn test_references(#[case] filename: &str, #[case] code: &str, #[case] expected: Vec<&str>) {
+    let references = references(filename, code);
+    assert_eq!(references.len(), 1);
+    assert_eq!(references[0], expected);
+}
+
+#[test]
+fn test_references_per_chunk() {
+    let code = r#"def first():
+    return load()
+
+
+def second():
+    return load() + save()
+"#;
+    let options = SplitOptions {
+        chunk_line_limit: 3,
+        collect_references: true,
+        ..Default::default()
+    };
+    let chunks = split("test.py", code, &options).unwrap();
+    assert_eq!(chunks.len(), 2);
+    let calls = chunks
+        .iter()
+        .map(|chunk| {
+            chunk
+                .references
+                .iter()
+                .filter(|reference| reference.kind == ReferenceKind::Call)
+                .map(|reference| (reference.name.as_str(), reference.line))
+                .collect::<Vec<(&str, usize)>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        calls,
+        vec![vec![("load", 1)], vec![("load", 5), ("save", 5)]]
+    );
+}
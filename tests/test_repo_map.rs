@@ -0,0 +1,105 @@
+//
+// test_repo_map.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(feature = "lang-python")]
+
+use devgen_splitter::{
+    repo_map,
+    repo_map_dir,
+    ApproximateTokenizer,
+    SplitError,
+    WalkOptions,
+};
+
+#[test]
+fn test_repo_map_dir() {
+    let result = repo_map_dir("tests/cases/repo_map", &WalkOptions::default()).unwrap();
+    assert!(result.errors.is_empty());
+    let map = result.map;
+    let paths = map
+        .outlines
+        .iter()
+        .map(|(path, _)| path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        vec!["geometry/report.py", "geometry/shapes.py", "main.py"]
+    );
+    let ranked = map
+        .entities
+        .iter()
+        .map(|entity| (entity.path.as_str(), entity.name.as_str()))
+        .collect::<Vec<_>>();
+    // `area` is defined by both classes, `Circle` is used by the two other files
+    assert_eq!(ranked[0], ("geometry/shapes.py", "Circle"));
+    // nothing uses `main` and `unit_circle`
+    assert_eq!(
+        &ranked[ranked.len() - 2..],
+        &[("geometry/shapes.py", "unit_circle"), ("main.py", "main")]
+    );
+}
+
+#[test]
+fn test_repo_map_render() {
+    let map = repo_map_dir("tests/cases/repo_map", &WalkOptions::default())
+        .unwrap()
+        .map;
+    assert_eq!(
+        map.render(2, 1000, &ApproximateTokenizer),
+        "geometry/shapes.py:\nclass Shape: ...\nclass Circle(Shape): ..."
+    );
+    // the budget only fits the file header and the first signature
+    assert_eq!(
+        map.render(10, 12, &ApproximateTokenizer),
+        "geometry/shapes.py:\nclass Circle(Shape): ..."
+    );
+    assert_eq!(
+        map.render(5, 1000, &ApproximateTokenizer),
+        "geometry/report.py:\ndef total_area(shapes): ...\n\ngeometry/shapes.py:\nclass Shape:\n    def area(self): ...\nclass Circle(Shape):\n    def area(self): ..."
+    );
+    assert_eq!(map.render(0, 1000, &ApproximateTokenizer), "");
+}
+
+#[test]
+fn test_repo_map_unsupported_files() {
+    let map = repo_map(vec![
+        ("notes.txt", "Circle"),
+        ("a.py", "def f():\n    pass\n"),
+    ]);
+    assert_eq!(map.outlines.len(), 1);
+    assert_eq!(map.entities.len(), 1);
+}
+
+#[test]
+fn test_repo_map_dir_threads() {
+    let map = |threads| {
+        let options = WalkOptions {
+            threads,
+            ..Default::default()
+        };
+        repo_map_dir("tests/cases/repo_map", &options).unwrap().map
+    };
+    assert_eq!(map(1), map(4));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_repo_map_dir_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.py"), "def f():\n    pass\n").unwrap();
+    std::os::unix::fs::symlink(dir.path().join("missing.py"), dir.path().join("broken.py"))
+        .unwrap();
+    let options = WalkOptions {
+        follow_links: true,
+        ..Default::default()
+    };
+    let result = repo_map_dir(dir.path(), &options).unwrap();
+    assert_eq!(result.map.outlines.len(), 1);
+    assert_eq!(result.errors.len(), 1);
+    let (path, e) = &result.errors[0];
+    assert_eq!(path, &dir.path().join("broken.py"));
+    assert!(matches!(e, SplitError::Io(_)));
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-ruby")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,7 +10,7 @@ use rstest::rstest;
 #[case(
     "example.rb",
     include_str!("./cases/ruby/example.rb"),
-    SplitOptions { chunk_line_limit: 10},
+    SplitOptions { chunk_line_limit: 10, ..Default::default() },
     2
 )]
 fn test_ruby_split(
@@ -19,7 +21,7 @@ fn test_ruby_split(
 ) {
     let result = split(filename, code, &options);
     println!("result: {:?}", result);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
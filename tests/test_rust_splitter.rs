@@ -1,6 +1,10 @@
+#![cfg(feature = "lang-rust")]
+
 use devgen_splitter::{
     split,
+    ChunkOverlap,
     SplitOptions,
+    SplitStrategy,
 };
 use rstest::rstest;
 
@@ -8,43 +12,43 @@ use rstest::rstest;
 #[case(
     "rust_function_test.rs",
     include_str!("./cases/rust/rust_function_test.rs"),
-    SplitOptions { chunk_line_limit: 40},
+    SplitOptions { chunk_line_limit: 40, ..Default::default() },
     1
 )]
 #[case(
     "rust_function_in_mod.rs",
     include_str!("./cases/rust/rust_function_in_mod.rs"),
-    SplitOptions { chunk_line_limit: 40},
+    SplitOptions { chunk_line_limit: 40, ..Default::default() },
     3
 )]
 #[case(
     "rust_long_function.rs",
     include_str!("./cases/rust/rust_long_function.rs"),
-    SplitOptions { chunk_line_limit: 40 },
+    SplitOptions { chunk_line_limit: 40, ..Default::default() },
     4
 )]
 #[case(
     "rust_tonic_case.rs",
     include_str!("./cases/rust/rust_tonic_case.rs"),
-    SplitOptions { chunk_line_limit: 40 },
+    SplitOptions { chunk_line_limit: 40, ..Default::default() },
     10
 )]
 #[case(
     "rust_similar_case.rs",
     include_str!("./cases/rust/rust_similar_case.rs"),
-    SplitOptions { chunk_line_limit: 30 },
+    SplitOptions { chunk_line_limit: 30, ..Default::default() },
     18
 )]
 #[case(
     "rust_method_for_incomplete_block.rs",
     include_str!("./cases/rust/rust_method_for_incomplete_block.rs"),
-    SplitOptions { chunk_line_limit: 30 },
+    SplitOptions { chunk_line_limit: 30, ..Default::default() },
     6
 )]
 #[case(
     "large_field.rs",
     include_str!("./cases/rust/large_field.rs"),
-    SplitOptions { chunk_line_limit: 30 },
+    SplitOptions { chunk_line_limit: 30, ..Default::default() },
     74
 )]
 fn test_rust_split(
@@ -54,7 +58,7 @@ fn test_rust_split(
     #[case] expected: usize,
 ) {
     let result = split(filename, code, &options);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     assert_eq!(result.len(), expected);
     let lines = code.lines().collect::<Vec<&str>>();
@@ -67,3 +71,133 @@ fn test_rust_split(
         println!("-------------------------------");
     }
 }
+
+#[rstest]
+#[case(ChunkOverlap::Lines(3), 3)]
+#[case(ChunkOverlap::Ratio(0.1), 7)]
+fn test_rust_split_with_overlap(#[case] overlap: ChunkOverlap, #[case] max_overlap_lines: usize) {
+    let code = include_str!("./cases/rust/rust_long_function.rs");
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        ..Default::default()
+    };
+    let chunks = split("rust_long_function.rs", code, &options).unwrap();
+    let overlap_options = SplitOptions {
+        chunk_line_limit: 40,
+        overlap: Some(overlap),
+        ..Default::default()
+    };
+    let overlap_chunks = split("rust_long_function.rs", code, &overlap_options).unwrap();
+    assert_eq!(chunks.len(), overlap_chunks.len());
+    for (chunk, overlap_chunk) in chunks.iter().zip(overlap_chunks.iter()) {
+        assert!(overlap_chunk.line_range.start <= chunk.line_range.start);
+        assert!(overlap_chunk.line_range.end >= chunk.line_range.end);
+        assert!(chunk.line_range.start - overlap_chunk.line_range.start <= max_overlap_lines);
+        assert!(overlap_chunk.line_range.end - chunk.line_range.end <= max_overlap_lines);
+        for entity in &chunk.entities {
+            assert!(overlap_chunk.entities.iter().any(|e| e.name == entity.name));
+        }
+    }
+    assert!(overlap_chunks
+        .windows(2)
+        .any(|pair| pair[1].line_range.start < pair[0].line_range.end));
+    // the first chunk is extended into the enum definition that follows it
+    assert!(overlap_chunks[0]
+        .entities
+        .iter()
+        .any(|entity| entity.name == "EntityType"));
+}
+
+#[rstest]
+#[case("rust_function_in_mod.rs", include_str!("./cases/rust/rust_function_in_mod.rs"), 40, 5)]
+#[case("rust_long_function.rs", include_str!("./cases/rust/rust_long_function.rs"), 30, 10)]
+#[case("rust_similar_case.rs", include_str!("./cases/rust/rust_similar_case.rs"), 30, 10)]
+fn test_rust_split_with_min_chunk_lines(
+    #[case] filename: &str,
+    #[case] code: &str,
+    #[case] chunk_line_limit: usize,
+    #[case] min_chunk_lines: usize,
+) {
+    let default_options = SplitOptions {
+        chunk_line_limit,
+        ..Default::default()
+    };
+    let default_chunks = split(filename, code, &default_options).unwrap();
+    let options = SplitOptions {
+        chunk_line_limit,
+        min_chunk_lines: Some(min_chunk_lines),
+        ..Default::default()
+    };
+    let chunks = split(filename, code, &options).unwrap();
+    assert!(chunks.len() < default_chunks.len());
+    for chunk in &chunks {
+        assert!(
+            chunk.line_range.len() >= min_chunk_lines,
+            "chunk {:?} is too small",
+            chunk.line_range
+        );
+    }
+    for pair in chunks.windows(2) {
+        assert_eq!(pair[0].line_range.end, pair[1].line_range.start);
+    }
+}
+
+#[rstest]
+#[case("rust_similar_case.rs", include_str!("./cases/rust/rust_similar_case.rs"), 30)]
+#[case("rust_tonic_case.rs", include_str!("./cases/rust/rust_tonic_case.rs"), 40)]
+#[case("large_field.rs", include_str!("./cases/rust/large_field.rs"), 30)]
+fn test_rust_split_with_balanced_strategy(
+    #[case] filename: &str,
+    #[case] code: &str,
+    #[case] chunk_line_limit: usize,
+) {
+    let greedy_options = SplitOptions {
+        chunk_line_limit,
+        ..Default::default()
+    };
+    let greedy_chunks = split(filename, code, &greedy_options).unwrap();
+    let options = SplitOptions {
+        chunk_line_limit,
+        strategy: SplitStrategy::Balanced,
+        ..Default::default()
+    };
+    let chunks = split(filename, code, &options).unwrap();
+    let spread = |chunks: &[devgen_splitter::Chunk]| {
+        let sizes = chunks.iter().map(|chunk| chunk.line_range.len());
+        sizes.clone().max().unwrap() - sizes.min().unwrap()
+    };
+    assert!(spread(&chunks) <= spread(&greedy_chunks));
+    assert_eq!(chunks[0].line_range.start, 0);
+    assert_eq!(chunks.last().unwrap().line_range.end, code.lines().count());
+    for pair in chunks.windows(2) {
+        assert_eq!(pair[0].line_range.end, pair[1].line_range.start);
+    }
+    for chunk in &chunks {
+        assert!(chunk.line_range.len() <= chunk_line_limit);
+    }
+}
+
+#[rstest]
+#[case("rust_similar_case.rs", include_str!("./cases/rust/rust_similar_case.rs"))]
+#[case("rust_long_function.rs", include_str!("./cases/rust/rust_long_function.rs"))]
+fn test_rust_chunk_ids_survive_line_shifts(#[case] filename: &str, #[case] code: &str) {
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        ..Default::default()
+    };
+    let chunks = split(filename, code, &options).unwrap();
+    let shifted_code = format!("\n\n{}", code);
+    let shifted_chunks = split(filename, &shifted_code, &options).unwrap();
+    let ids = |chunks: &[devgen_splitter::Chunk]| {
+        chunks
+            .iter()
+            .map(|chunk| chunk.id.clone())
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(ids(&chunks), ids(&shifted_chunks));
+    let other_chunks = split(&format!("other_{}", filename), code, &options).unwrap();
+    for (chunk, other) in chunks.iter().zip(other_chunks.iter()) {
+        assert_ne!(chunk.id, other.id);
+        assert_eq!(chunk.content_hash, other.content_hash);
+    }
+}
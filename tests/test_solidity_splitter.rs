@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-solidity")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,7 +10,7 @@ use rstest::rstest;
 #[case(
     "solidity_test.sol",
     include_str!("./cases/solidity/test_db3_meta.sol"),
-    SplitOptions { chunk_line_limit: 40},
+    SplitOptions { chunk_line_limit: 40, ..Default::default() },
     6
 )]
 fn test_solidity_splitter(
@@ -19,7 +21,7 @@ fn test_solidity_splitter(
 ) {
     let result = split(filename, code, &options);
     println!("result: {:?}", result);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
@@ -0,0 +1,112 @@
+//
+// test_split_diagnostics.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(all(
+    feature = "lang-python",
+    feature = "lang-rust",
+    feature = "lang-toml",
+    feature = "lang-typescript"
+))]
+
+use devgen_splitter::{
+    split_with_diagnostics,
+    validate_chunks,
+    SplitOptions,
+    SplitResult,
+    SyntaxErrorKind,
+};
+use rstest::rstest;
+
+fn entity_names(result: &SplitResult) -> Vec<String> {
+    let mut names = result
+        .chunks
+        .iter()
+        .flat_map(|chunk| chunk.entities.iter().map(|entity| entity.name.clone()))
+        .collect::<Vec<String>>();
+    names.dedup();
+    names
+}
+
+#[test]
+fn test_clean_code_has_no_diagnostics() {
+    let code = include_str!("./cases/rust/rust_long_function.rs");
+    let options = SplitOptions {
+        chunk_line_limit: 5,
+        ..Default::default()
+    };
+    let result = split_with_diagnostics("rust_long_function.rs", code, &options).unwrap();
+    assert!(result.diagnostics.syntax_errors.is_empty());
+    assert_eq!(result.diagnostics.failed_entities, 0);
+    assert!(!result.diagnostics.line_fallback);
+    assert!(result.diagnostics.masked_line_ranges.is_empty());
+}
+
+#[test]
+fn test_line_fallback() {
+    let code = include_str!("./cases/toml/test.toml");
+    let options = SplitOptions {
+        chunk_line_limit: 5,
+        ..Default::default()
+    };
+    let result = split_with_diagnostics("test.toml", code, &options).unwrap();
+    assert!(result.diagnostics.line_fallback);
+}
+
+#[rstest]
+#[case(
+    "broken.ts",
+    "function a() {\n  run();\n}\n\nfunction broken( {\n  let x = ;\n}\n\nfunction c() {\n  run();\n}\n\nclass D {\n  e() {\n    run();\n  }\n}\n",
+    vec!["a"],
+    vec!["a", "c", "e"]
+)]
+#[case(
+    "broken.py",
+    "def a():\n    run()\n\ndef broken(:\n    pass\n\ndef c():\n    run()\n\nclass D:\n    def e(self):\n        pass\n",
+    vec!["a", "broken", "c", "e"],
+    vec!["a", "broken", "c", "e"]
+)]
+fn test_tolerate_errors(
+    #[case] filename: &str,
+    #[case] code: &str,
+    #[case] strict_names: Vec<&str>,
+    #[case] tolerant_names: Vec<&str>,
+) {
+    let options = SplitOptions {
+        chunk_line_limit: 5,
+        ..Default::default()
+    };
+    let strict = split_with_diagnostics(filename, code, &options).unwrap();
+    assert!(!strict.diagnostics.syntax_errors.is_empty());
+    assert!(strict.diagnostics.masked_line_ranges.is_empty());
+    assert_eq!(entity_names(&strict), strict_names);
+
+    let options = SplitOptions {
+        tolerate_errors: true,
+        ..options
+    };
+    let tolerant = split_with_diagnostics(filename, code, &options).unwrap();
+    assert_eq!(
+        tolerant.diagnostics.syntax_errors,
+        strict.diagnostics.syntax_errors
+    );
+    assert!(!tolerant.diagnostics.masked_line_ranges.is_empty());
+    assert_eq!(entity_names(&tolerant), tolerant_names);
+    assert!(validate_chunks(code, &tolerant.chunks).is_empty());
+}
+
+#[test]
+fn test_missing_node() {
+    let code = "fn main() {\n    let x = 1\n}\n";
+    let options = SplitOptions {
+        chunk_line_limit: 5,
+        ..Default::default()
+    };
+    let result = split_with_diagnostics("main.rs", code, &options).unwrap();
+    assert!(result.diagnostics.syntax_errors.iter().any(
+        |error| matches!(&error.kind, SyntaxErrorKind::Missing { node_kind } if node_kind == ";")
+            && error.line_range == (1..2)
+    ));
+}
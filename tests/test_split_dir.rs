@@ -0,0 +1,178 @@
+#![cfg(all(feature = "lang-bash", feature = "lang-python", feature = "lang-rust"))]
+
+use devgen_splitter::{
+    split_dir,
+    SplitError,
+    SplitOptions,
+    Splitter,
+    WalkOptions,
+};
+use rstest::rstest;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::Path,
+    sync::Arc,
+};
+
+fn write(root: &Path, name: &str, content: &str) {
+    let path = root.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[rstest]
+#[case(WalkOptions::default(), vec!["bin/deploy", "lib.rs", "main.py"])]
+#[case(WalkOptions { threads: 1, ..Default::default() }, vec!["bin/deploy", "lib.rs", "main.py"])]
+#[case(
+    WalkOptions { include: vec!["*.py".to_string()], ..Default::default() },
+    vec!["main.py"]
+)]
+#[case(
+    WalkOptions { exclude: vec!["*.py".to_string()], ..Default::default() },
+    vec!["bin/deploy", "lib.rs"]
+)]
+#[case(
+    WalkOptions { respect_ignore_files: false, include_hidden: true, ..Default::default() },
+    vec![".hidden.rs", "bin/deploy", "generated/out.rs", "large.rs", "lib.rs", "main.py"]
+)]
+fn test_split_dir(#[case] options: WalkOptions, #[case] expected: Vec<&str>) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, ".gitignore", "generated/\n");
+    write(root, ".ignore", "large.rs\n");
+    write(
+        root,
+        "lib.rs",
+        include_str!("./cases/rust/rust_function_test.rs"),
+    );
+    write(root, "main.py", "def main():\n    print('hello')\n");
+    write(root, "generated/out.rs", "fn generated() {}\n");
+    write(
+        root,
+        "large.rs",
+        include_str!("./cases/rust/large_field.rs"),
+    );
+    write(root, ".hidden.rs", "fn hidden() {}\n");
+    write(root, "notes.xyz", "not a supported language\n");
+    write(root, "bin/deploy", "#!/usr/bin/env bash\necho deploy\n");
+    write(root, "bin/README", "not a script\n");
+    let splitter = Arc::new(
+        Splitter::builder()
+            .options(SplitOptions {
+                chunk_line_limit: 40,
+                ..Default::default()
+            })
+            .build(),
+    );
+    let results = split_dir(root, splitter, &options)
+        .unwrap()
+        .map(|(path, chunks)| {
+            let name = path
+                .strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/");
+            (name, chunks)
+        })
+        .collect::<BTreeMap<_, _>>();
+    assert_eq!(results.keys().collect::<Vec<_>>(), expected);
+    for (name, chunks) in results {
+        assert!(!chunks.unwrap().is_empty(), "{} has no chunks", name);
+    }
+}
+
+#[test]
+fn test_split_dir_max_file_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "small.rs", "fn small() {}\n");
+    write(
+        root,
+        "large.rs",
+        include_str!("./cases/rust/large_field.rs"),
+    );
+    let splitter = Arc::new(
+        Splitter::builder()
+            .options(SplitOptions {
+                chunk_line_limit: 40,
+                ..Default::default()
+            })
+            .build(),
+    );
+    let options = WalkOptions {
+        max_file_size: Some(1024),
+        ..Default::default()
+    };
+    let paths = split_dir(root, splitter, &options)
+        .unwrap()
+        .map(|(path, _)| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, vec!["small.rs"]);
+}
+
+#[test]
+fn test_split_dir_isolates_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "good.rs", "fn good() {}\n");
+    fs::write(root.join("bad.rs"), [0xff, 0xfe, 0x00]).unwrap();
+    let splitter = Arc::new(
+        Splitter::builder()
+            .options(SplitOptions {
+                chunk_line_limit: 40,
+                ..Default::default()
+            })
+            .build(),
+    );
+    let results = split_dir(root, splitter, &WalkOptions::default())
+        .unwrap()
+        .map(|(path, chunks)| {
+            (
+                path.file_name().unwrap().to_string_lossy().to_string(),
+                chunks.is_ok(),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+    assert_eq!(
+        results,
+        BTreeMap::from([("bad.rs".to_string(), false), ("good.rs".to_string(), true)])
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_split_dir_reports_walk_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write(root, "good.rs", "fn good() {}\n");
+    std::os::unix::fs::symlink(root.join("missing.rs"), root.join("broken.rs")).unwrap();
+    let splitter = Arc::new(
+        Splitter::builder()
+            .options(SplitOptions {
+                chunk_line_limit: 40,
+                ..Default::default()
+            })
+            .build(),
+    );
+    let options = WalkOptions {
+        follow_links: true,
+        ..Default::default()
+    };
+    let results = split_dir(root, splitter, &options)
+        .unwrap()
+        .map(|(path, chunks)| {
+            (
+                path.file_name().unwrap().to_string_lossy().to_string(),
+                matches!(chunks, Err(SplitError::Io(_))),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+    assert_eq!(
+        results,
+        BTreeMap::from([
+            ("broken.rs".to_string(), true),
+            ("good.rs".to_string(), false)
+        ])
+    );
+}
@@ -0,0 +1,70 @@
+//
+// test_split_error.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(feature = "lang-rust")]
+
+use devgen_splitter::{
+    rechunk_with_diff,
+    split,
+    ChunkOverlap,
+    QueryErrorKind,
+    SplitError,
+    SplitOptions,
+    Splitter,
+};
+use rstest::rstest;
+
+#[test]
+fn test_unsupported_language() {
+    let options = SplitOptions {
+        chunk_line_limit: 10,
+        ..Default::default()
+    };
+    let result = split("notes.unknown", "hello", &options);
+    assert!(matches!(
+        result,
+        Err(SplitError::UnsupportedLanguage { filename }) if filename == "notes.unknown"
+    ));
+}
+
+#[rstest]
+#[case(SplitOptions { chunk_line_limit: 0, ..Default::default() })]
+#[case(SplitOptions { chunk_line_limit: 10, min_chunk_lines: Some(20), ..Default::default() })]
+#[case(SplitOptions { chunk_line_limit: 10, overlap: Some(ChunkOverlap::Ratio(f32::NAN)), ..Default::default() })]
+fn test_invalid_options(#[case] options: SplitOptions) {
+    let result = split("main.rs", "fn main() {}", &options);
+    assert!(matches!(result, Err(SplitError::InvalidOptions { .. })));
+}
+
+#[test]
+fn test_query_error() {
+    let options = SplitOptions {
+        chunk_line_limit: 10,
+        ..Default::default()
+    };
+    let splitter = Splitter::builder()
+        .options(options)
+        .query("Rust", "(function_item name: (identifier) @function.name")
+        .build();
+    let result = splitter.split("main.rs", "fn main() {}");
+    assert!(matches!(
+        result,
+        Err(SplitError::QueryError { lang, kind: QueryErrorKind::Syntax, .. }) if lang == "Rust"
+    ));
+}
+
+#[test]
+fn test_io_and_diff_errors() {
+    let options = SplitOptions {
+        chunk_line_limit: 10,
+        ..Default::default()
+    };
+    let splitter = Splitter::builder().options(options.clone()).build();
+    let result = splitter.split_path("./tests/cases/rust/missing.rs");
+    assert!(matches!(result, Err(SplitError::Io(_))));
+    let result = rechunk_with_diff("main.rs", "fn main() {}", "@@ -a +1 @@", &[], &options);
+    assert!(matches!(result, Err(SplitError::InvalidDiff { .. })));
+}
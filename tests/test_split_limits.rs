@@ -0,0 +1,116 @@
+//
+// test_split_limits.rs
+// Copyright (C) 2024 imotai <codego.me@gmail.com>
+// Distributed under terms of the MIT license.
+//
+
+#![cfg(feature = "lang-rust")]
+
+use devgen_splitter::{
+    split,
+    split_with_diagnostics,
+    validate_chunks,
+    IncrementalSplitter,
+    Limit,
+    LimitAction,
+    SplitError,
+    SplitLimits,
+    SplitOptions,
+    Splitter,
+};
+use rstest::rstest;
+use std::time::Duration;
+
+#[rstest]
+#[case(SplitLimits { max_file_bytes: Some(1024), ..Default::default() }, Limit::FileBytes)]
+#[case(SplitLimits { max_line_length: Some(40), ..Default::default() }, Limit::LineLength)]
+#[case(SplitLimits { max_tree_depth: Some(5), ..Default::default() }, Limit::TreeDepth)]
+#[case(SplitLimits { parse_timeout: Some(Duration::from_micros(1)), ..Default::default() }, Limit::ParseTimeout)]
+fn test_split_limits(#[case] limits: SplitLimits, #[case] limit: Limit) {
+    let code = include_str!("./cases/rust/rust_tonic_case.rs");
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        limits: limits.clone(),
+        ..Default::default()
+    };
+    let result = split("rust_tonic_case.rs", code, &options);
+    match limit {
+        Limit::ParseTimeout => assert!(matches!(result, Err(SplitError::Timeout))),
+        limit => assert!(
+            matches!(result, Err(SplitError::LimitExceeded { limit: exceeded }) if exceeded == limit)
+        ),
+    }
+
+    let options = SplitOptions {
+        limits: SplitLimits {
+            on_exceeded: LimitAction::SplitByLines,
+            ..limits
+        },
+        ..options
+    };
+    let result = split_with_diagnostics("rust_tonic_case.rs", code, &options).unwrap();
+    assert_eq!(result.diagnostics.exceeded_limit, Some(limit));
+    assert!(result.diagnostics.line_fallback);
+    assert!(validate_chunks(code, &result.chunks).is_empty());
+    assert!(result
+        .chunks
+        .iter()
+        .all(|chunk| chunk.line_range.len() <= 40 && chunk.entities.is_empty()));
+
+    // the parser is still usable after a limit is exceeded
+    let options = SplitOptions {
+        limits: SplitLimits::default(),
+        ..options
+    };
+    let chunks = split("rust_tonic_case.rs", code, &options).unwrap();
+    assert!(chunks.iter().any(|chunk| !chunk.entities.is_empty()));
+}
+
+#[test]
+fn test_limits_within_bounds() {
+    let code = include_str!("./cases/rust/rust_long_function.rs");
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        limits: SplitLimits {
+            max_file_bytes: Some(1 << 20),
+            max_line_length: Some(1000),
+            max_tree_depth: Some(200),
+            parse_timeout: Some(Duration::from_secs(10)),
+            on_exceeded: LimitAction::Error,
+        },
+        ..Default::default()
+    };
+    let limited = split("rust_long_function.rs", code, &options).unwrap();
+    let options = SplitOptions {
+        limits: SplitLimits::default(),
+        ..options
+    };
+    let unlimited = split("rust_long_function.rs", code, &options).unwrap();
+    assert_eq!(limited, unlimited);
+}
+
+#[test]
+fn test_splitter_and_incremental_limits() {
+    let code = include_str!("./cases/rust/rust_long_function.rs");
+    let options = SplitOptions {
+        chunk_line_limit: 40,
+        limits: SplitLimits {
+            max_file_bytes: Some(1024),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let splitter = Splitter::builder().options(options.clone()).build();
+    assert!(matches!(
+        splitter.split("rust_long_function.rs", code),
+        Err(SplitError::LimitExceeded {
+            limit: Limit::FileBytes
+        })
+    ));
+    assert!(matches!(
+        IncrementalSplitter::new("rust_long_function.rs", code, &options),
+        Err(SplitError::LimitExceeded {
+            limit: Limit::FileBytes
+        })
+    ));
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-sql")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,13 +10,13 @@ use rstest::rstest;
 #[case(
     "test.sql",
     include_str!("./cases/sql/test_sql.sql"),
-    SplitOptions { chunk_line_limit: 10},
+    SplitOptions { chunk_line_limit: 10, ..Default::default() },
     1
 )]
 #[case(
     "test.sql",
     include_str!("./cases/sql/test_sql_large_query.sql"),
-    SplitOptions { chunk_line_limit: 10},
+    SplitOptions { chunk_line_limit: 10, ..Default::default() },
     2
 )]
 fn test_sql_split(
@@ -25,7 +27,7 @@ fn test_sql_split(
 ) {
     let result = split(filename, code, &options);
     println!("result: {:?}", result);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
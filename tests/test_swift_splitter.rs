@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-swift")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,13 +10,13 @@ use rstest::rstest;
 #[case(
     "test.swift",
     include_str!("./cases/swift/test.swift"),
-    SplitOptions { chunk_line_limit: 10},
+    SplitOptions { chunk_line_limit: 10, ..Default::default() },
     3
 )]
 #[case(
     "test.swift",
     include_str!("./cases/swift/GameView.swift"),
-    SplitOptions { chunk_line_limit: 10},
+    SplitOptions { chunk_line_limit: 10, ..Default::default() },
     11
 )]
 fn test_swift_split(
@@ -25,7 +27,7 @@ fn test_swift_split(
 ) {
     let result = split(filename, code, &options);
     println!("result: {:?}", result);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {
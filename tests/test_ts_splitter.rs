@@ -1,3 +1,5 @@
+#![cfg(feature = "lang-typescript")]
+
 use devgen_splitter::{
     split,
     SplitOptions,
@@ -8,20 +10,20 @@ use rstest::rstest;
 #[case(
     "ts_function_test.ts",
     include_str!("./cases/ts/typescript_function_test.ts"),
-    SplitOptions { chunk_line_limit: 40},
+    SplitOptions { chunk_line_limit: 40, ..Default::default() },
     3
 
 )]
 #[case(
     "ts_react_test.tsx",
     include_str!("./cases/ts/typescript_react_test.ts"),
-    SplitOptions { chunk_line_limit: 40},
+    SplitOptions { chunk_line_limit: 40, ..Default::default() },
     6
 )]
 #[case(
     "ts_function_class.ts",
     include_str!("./cases/ts/typescript_function_class.ts"),
-    SplitOptions { chunk_line_limit: 30},
+    SplitOptions { chunk_line_limit: 30, ..Default::default() },
     3
 )]
 fn test_ts_split(
@@ -32,7 +34,7 @@ fn test_ts_split(
 ) {
     let result = split(filename, code, &options);
     println!("result: {:?}", result);
-    assert_eq!(result.is_ok(), true);
+    assert!(result.is_ok());
     let result = result.unwrap();
     let lines = code.lines().collect::<Vec<&str>>();
     for chunk in &result {